@@ -0,0 +1,98 @@
+use circlemaze::{
+    maze::{
+        algorithms::RandomWalk,
+        circular_grid,
+        components::{Bias, MazeRng, Placement},
+        maze_builder::build_maze,
+    },
+    Algorithm, MazeConfig,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::SeedableRng;
+
+/// Circles and inner slices of the grids to generate, from small to poster size.
+const SIZES: [(u32, u32); 4] = [(10, 4), (40, 4), (40, 16), (100, 4)];
+const MIN_DISTANCES: [f64; 2] = [0., 0.5];
+
+fn grid_and_dist(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grid_and_dist");
+    for (circles, inner_slices) in SIZES {
+        group.bench_function(format!("{}x{}", circles, inner_slices), |b| {
+            b.iter(|| {
+                let mut grid = circular_grid::build(circles - 1, inner_slices, 0.);
+                grid.dist(MazeRng::seed_from_u64(1))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn walls(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_maze");
+    group.sample_size(20);
+    for (circles, inner_slices) in SIZES {
+        for min_distance in MIN_DISTANCES {
+            let mut grid = circular_grid::build(circles - 1, inner_slices, min_distance);
+            let id = format!("{}x{}/{}", circles, inner_slices, min_distance);
+            group.bench_function(id, |b| {
+                b.iter_batched(
+                    || grid.dist(MazeRng::seed_from_u64(1)),
+                    |dist| {
+                        build_maze(
+                            dist,
+                            MazeRng::seed_from_u64(2),
+                            &RandomWalk,
+                            Bias::default(),
+                            Placement::Random,
+                            None,
+                        )
+                        .unwrap()
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Builds whole mazes with more and more rings, which used to take time
+/// quadratic in the number of grid coordinates.
+fn build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build");
+    group.sample_size(10);
+    for circles in [25, 50, 100, 200] {
+        let config = MazeConfig::new().seed(1).circles(circles).inner_slices(4);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(circles),
+            &config,
+            |b, config| b.iter(|| config.build().unwrap()),
+        );
+    }
+    group.finish();
+}
+
+/// Builds a maze of 200 circles with every algorithm. Aldous-Broder gets a
+/// single inner slice to stay within the cells it allows.
+fn algorithms(c: &mut Criterion) {
+    let mut group = c.benchmark_group("algorithms");
+    group.sample_size(10);
+    for algorithm in Algorithm::ALL {
+        let inner_slices = if algorithm == Algorithm::AldousBroder {
+            1
+        } else {
+            4
+        };
+        let config = MazeConfig::new()
+            .seed(1)
+            .circles(200)
+            .inner_slices(inner_slices)
+            .algorithm(algorithm);
+        let id = format!("{:?}/200x{}", algorithm, inner_slices);
+        group.bench_function(id, |b| b.iter(|| config.build().unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, grid_and_dist, walls, build, algorithms);
+criterion_main!(benches);
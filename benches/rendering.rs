@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use circlemaze::{
+    svg::{
+        draw::{SvgCanvas, RADIUS_INNER_CIRCLE},
+        parse::Parser,
+    },
+    Maze, MazeConfig,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// Circles and inner slices of the mazes to render, from small to poster size.
+const SIZES: [(u32, u32); 4] = [(10, 4), (40, 4), (40, 16), (100, 4)];
+
+fn mazes() -> Vec<(String, Maze)> {
+    SIZES
+        .iter()
+        .map(|&(circles, inner_slices)| {
+            let maze = MazeConfig::new()
+                .seed(1)
+                .circles(circles)
+                .inner_slices(inner_slices)
+                .build()
+                .unwrap();
+            (format!("{}x{}", circles, inner_slices), maze)
+        })
+        .collect()
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_svg");
+    for (id, maze) in mazes() {
+        let parser = Parser::new(&maze.radii(), RADIUS_INNER_CIRCLE, maze.borders());
+        group.bench_function(id, |b| {
+            b.iter(|| parser.parse(SvgCanvas::default()).path().len())
+        });
+    }
+    group.finish();
+}
+
+fn write_svg(c: &mut Criterion) {
+    let template = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/maze.template.svg");
+    let output = std::env::temp_dir().join("circlemaze-bench.svg");
+    let mut group = c.benchmark_group("write_svg");
+    for (id, maze) in mazes() {
+        group.bench_function(id, |b| {
+            b.iter(|| maze.write_svg(&template, &output).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn optimise(c: &mut Criterion) {
+    let mut group = c.benchmark_group("optimise");
+    for (id, maze) in mazes() {
+        group.bench_function(id, |b| b.iter(|| maze.drawing_order().pen_up_after()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, write_svg, optimise);
+criterion_main!(benches);
@@ -0,0 +1,255 @@
+use std::{fs, path::Path};
+
+use crate::{
+    maze::{components::Border, radii::Radii},
+    svg::{
+        draw::RADIUS_INNER_CIRCLE,
+        parse::{Canvas, CartesianCoord, Parser},
+    },
+    Result,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DxfOptions {
+    /// Width of an equally wide ring in millimetres.
+    pub scale: f64,
+}
+
+impl Default for DxfOptions {
+    fn default() -> Self {
+        DxfOptions { scale: 10. }
+    }
+}
+
+/// Writes the borders as DXF entities in millimetres. The origin is the lower
+/// left corner of the square around the maze and the y axis points up.
+pub fn draw(radii: &Radii, borders: &[Border], options: &DxfOptions, output: &Path) -> Result<()> {
+    fs::write(output, render(radii, borders, options))?;
+    Ok(())
+}
+
+fn render(radii: &Radii, borders: &[Border], options: &DxfOptions) -> String {
+    let parser = Parser::new(radii, RADIUS_INNER_CIRCLE, borders);
+    let canvas = parser.parse(DxfCanvas::new(options, parser.center));
+
+    let mut dxf = String::new();
+    for group in [
+        "0",
+        "SECTION",
+        "2",
+        "HEADER",
+        "9",
+        "$INSUNITS",
+        "70",
+        "4",
+        "9",
+        "$MEASUREMENT",
+        "70",
+        "1",
+        "0",
+        "ENDSEC",
+        "0",
+        "SECTION",
+        "2",
+        "ENTITIES",
+    ] {
+        dxf.push_str(group);
+        dxf.push('\n');
+    }
+    dxf.push_str(&canvas.entities);
+    for group in ["0", "ENDSEC", "0", "EOF"] {
+        dxf.push_str(group);
+        dxf.push('\n');
+    }
+    dxf
+}
+
+struct DxfCanvas {
+    mm: f64,
+    center: CartesianCoord,
+    current: CartesianCoord,
+    entities: String,
+}
+
+impl DxfCanvas {
+    fn new(options: &DxfOptions, center: CartesianCoord) -> Self {
+        DxfCanvas {
+            mm: options.scale / RADIUS_INNER_CIRCLE as f64,
+            center,
+            current: center,
+            entities: String::new(),
+        }
+    }
+
+    /// Position in millimetres, with the y axis pointing up.
+    fn mm(&self, coord: CartesianCoord) -> CartesianCoord {
+        (coord.0 * self.mm, (self.center.1 * 2. - coord.1) * self.mm)
+    }
+
+    /// Angle in degrees of a point around the centre, anticlockwise from the x axis.
+    fn degrees(&self, coord: CartesianCoord) -> f64 {
+        (self.center.1 - coord.1)
+            .atan2(coord.0 - self.center.0)
+            .to_degrees()
+            .rem_euclid(360.)
+    }
+
+    fn entity(&mut self, kind: &str, groups: &[(u16, f64)]) {
+        self.entities.push_str(&format!("0\n{}\n8\n0\n", kind));
+        for (code, value) in groups {
+            self.entities
+                .push_str(&format!("{}\n{}\n", code, number(*value)));
+        }
+    }
+}
+
+/// Formats a value with six decimals, without a sign for zero.
+fn number(value: f64) -> String {
+    format!("{:.6}", (value * 1e6).round() / 1e6 + 0.)
+}
+
+impl Canvas for DxfCanvas {
+    fn move_to(mut self, coord: CartesianCoord) -> Self {
+        self.current = coord;
+        self
+    }
+
+    fn draw_arc(
+        mut self,
+        radius: f64,
+        _long_arc_flag: u8,
+        sweep_flag: u8,
+        coord: CartesianCoord,
+    ) -> Self {
+        // DXF arcs always run anticlockwise from the start angle to the end angle.
+        let (from, to) = if sweep_flag == 0 {
+            (self.current, coord)
+        } else {
+            (coord, self.current)
+        };
+        let center = self.mm(self.center);
+        let groups = [
+            (10, center.0),
+            (20, center.1),
+            (30, 0.),
+            (40, radius * self.mm),
+            (50, self.degrees(from)),
+            (51, self.degrees(to)),
+        ];
+        self.entity("ARC", &groups);
+        self.current = coord;
+        self
+    }
+
+    fn draw_circle(mut self, radius: f64, center: CartesianCoord) -> Self {
+        let center = self.mm(center);
+        let groups = [
+            (10, center.0),
+            (20, center.1),
+            (30, 0.),
+            (40, radius * self.mm),
+        ];
+        self.entity("CIRCLE", &groups);
+        self
+    }
+
+    fn draw_line(mut self, coord: CartesianCoord) -> Self {
+        let (from, to) = (self.mm(self.current), self.mm(coord));
+        let groups = [
+            (10, from.0),
+            (20, from.1),
+            (30, 0.),
+            (11, to.0),
+            (21, to.1),
+            (31, 0.),
+        ];
+        self.entity("LINE", &groups);
+        self.current = coord;
+        self
+    }
+}
+
+#[cfg(test)]
+mod draw_test {
+    use crate::maze::{
+        components::{Angle, Border, CircleCoordinate},
+        radii::Radii,
+    };
+
+    use super::{render, DxfOptions};
+
+    #[test]
+    fn test_entities() {
+        let coord = |circle, angle| CircleCoordinate { circle, angle };
+        let borders = vec![
+            Border {
+                start: coord(0, Angle::new(3_u32, 4_u32)),
+                end: coord(0, Angle::new(1_u32, 4_u32)),
+            },
+            Border {
+                start: coord(0, Angle::new(1_u32, 4_u32)),
+                end: coord(1, Angle::new(1_u32, 4_u32)),
+            },
+            Border {
+                start: coord(1, Angle::from(0)),
+                end: coord(1, Angle::from(0)),
+            },
+        ];
+        let options = DxfOptions { scale: 5. };
+        let dxf = render(&Radii::linear(1), &borders, &options);
+
+        let entities: Vec<&str> = dxf.split_once("ENTITIES\n").unwrap().1.lines().collect();
+        let expected = [
+            "0",
+            "ARC",
+            "8",
+            "0",
+            "10",
+            "10.000000",
+            "20",
+            "10.000000",
+            "30",
+            "0.000000",
+            "40",
+            "5.000000",
+            "50",
+            "270.000000",
+            "51",
+            "90.000000",
+            "0",
+            "LINE",
+            "8",
+            "0",
+            "10",
+            "10.000000",
+            "20",
+            "15.000000",
+            "30",
+            "0.000000",
+            "11",
+            "10.000000",
+            "21",
+            "20.000000",
+            "31",
+            "0.000000",
+            "0",
+            "CIRCLE",
+            "8",
+            "0",
+            "10",
+            "10.000000",
+            "20",
+            "10.000000",
+            "30",
+            "0.000000",
+            "40",
+            "10.000000",
+            "0",
+            "ENDSEC",
+            "0",
+            "EOF",
+        ];
+        assert_eq!(expected.to_vec(), entities);
+        assert!(dxf.starts_with("0\nSECTION\n2\nHEADER\n"));
+    }
+}
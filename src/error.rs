@@ -0,0 +1,71 @@
+use std::{fmt, io};
+
+/// Everything that can go wrong while building, loading or drawing a maze.
+#[derive(Debug)]
+pub enum Error {
+    /// A parameter of the maze or of an output is out of range.
+    InvalidParameter(String),
+    /// The walls could not be grown on the grid.
+    Generation(String),
+    /// A saved maze does not fit its own grid.
+    InvalidMaze(String),
+    Io(io::Error),
+    Json(serde_json::Error),
+    // Boxed, as handlebars' errors are much larger than the others.
+    Template(Box<handlebars::TemplateError>),
+    Render(Box<handlebars::RenderError>),
+    Image(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidParameter(message) => write!(f, "invalid parameter: {}", message),
+            Error::Generation(message) => write!(f, "cannot generate the maze: {}", message),
+            Error::InvalidMaze(message) => write!(f, "invalid maze: {}", message),
+            Error::Io(error) => write!(f, "{}", error),
+            Error::Json(error) => write!(f, "invalid JSON: {}", error),
+            Error::Template(error) => write!(f, "invalid template: {}", error),
+            Error::Render(error) => write!(f, "cannot render the template: {}", error),
+            Error::Image(message) => write!(f, "cannot write the image: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Json(error) => Some(error),
+            Error::Template(error) => Some(error.as_ref()),
+            Error::Render(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+impl From<handlebars::TemplateError> for Error {
+    fn from(error: handlebars::TemplateError) -> Self {
+        Error::Template(Box::new(error))
+    }
+}
+
+impl From<handlebars::RenderError> for Error {
+    fn from(error: handlebars::RenderError) -> Self {
+        Error::Render(Box::new(error))
+    }
+}
@@ -0,0 +1,32 @@
+//! Generates circular mazes and renders them.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use circlemaze::MazeConfig;
+//!
+//! # fn main() -> circlemaze::Result<()> {
+//! let maze = MazeConfig::new().circles(8).inner_slices(6).seed(42).build()?;
+//! maze.write_svg(Path::new("assets/maze.template.svg"), Path::new("maze.svg"))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Building fails with [`Error::InvalidParameter`] when a parameter is out of
+//! range, such as a maze without slices or a minimal distance of 1 or more.
+
+pub mod dxf;
+pub mod error;
+pub mod maze;
+pub mod pdf;
+pub mod plot;
+pub mod png;
+pub mod svg;
+
+pub use error::{Error, Result};
+pub use maze::algorithms::Algorithm;
+pub use maze::components::{Bias, Goal, Placement};
+pub use maze::config::{Maze, MazeConfig};
+pub use maze::metrics::{Difficulty, Metrics};
+pub use maze::radii::Spacing;
+pub use maze::subdivision::Subdivision;
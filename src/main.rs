@@ -1,14 +1,681 @@
-use maze::{maze_builder::build_maze, circular_grid};
-use svg::draw;
-
-mod maze;
-mod svg;
-
-fn main() {
-    let circles = 5;
-    let base_segments = 10;
-    let min_distance = 0.3;
-    let mut grid = circular_grid::build(circles - 1, base_segments, min_distance);
-    let borders = build_maze(grid.dist());
-    _ = draw::draw(circles as usize, borders);
+use std::path::PathBuf;
+
+use circlemaze::{
+    dxf::draw::DxfOptions,
+    pdf::{
+        self,
+        draw::{PageSize, PdfOptions},
+    },
+    plot::draw::PlotOptions,
+    png::draw::PngOptions,
+    Algorithm, Bias, Difficulty, Goal, Maze, MazeConfig, Metrics, Placement, Spacing, Subdivision,
+};
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+
+/// Generates a circular maze and writes it to a file.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// Number of circles, including the outer wall
+    #[arg(short, long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(2..))]
+    circles: u32,
+
+    /// Number of slices on the innermost circle
+    #[arg(short = 's', long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    inner_slices: u32,
+
+    /// How many cells each ring has: `interleaved` adds the inner slices with every ring,
+    /// `constant` keeps them, `doubling:<N>` doubles them every N rings and `proportional`
+    /// grows them with the ring, giving roughly square cells with 9 inner slices
+    #[arg(long, default_value = "interleaved", value_parser = parse_subdivision)]
+    subdivision: Subdivision,
+
+    /// How far apart the circles are: `linear` for equally wide rings, `logarithmic` for rings
+    /// that widen with their radius, or the radius of every circle as `<R1>,<R2>,...`
+    #[arg(long, default_value = "linear", value_parser = parse_spacing)]
+    spacing: Spacing,
+
+    /// Radius of the innermost circle in ring widths; above 1 leaves a larger room in the centre
+    #[arg(long, default_value_t = 1., value_parser = parse_positive)]
+    inner_radius: f64,
+
+    /// Minimal distance between two points on the same circle
+    #[arg(short, long, default_value_t = 0.3, value_parser = parse_min_distance)]
+    min_distance: f64,
+
+    /// Algorithm that grows the walls: `random-walk`, `recursive-backtracker`, `prim`,
+    /// `kruskal`, `wilson`, `aldous-broder` (up to 20000 cells), `growing-tree` or `hunt-and-kill`
+    #[arg(long, default_value = "random-walk", value_parser = parse_algorithm)]
+    algorithm: Algorithm,
+
+    /// Weights of the directions the walls grow in, as `<IN>,<OUT>,<CLOCKWISE>,<COUNTERCLOCKWISE>`;
+    /// high clockwise weights give long circular corridors, high in and out weights radial ones
+    #[arg(long, default_value = "1,1,1,1", value_parser = parse_weights)]
+    weights: [f64; 4],
+
+    /// How much more likely a wall keeps its direction than turning
+    #[arg(long, default_value_t = 1., value_parser = parse_weight)]
+    straight: f64,
+
+    /// Percentage of dead ends to remove by opening an extra wall, adding loops
+    #[arg(long, default_value_t = 0., value_parser = parse_percent)]
+    braid: f64,
+
+    /// Seed for the random number generator; a random seed is used when omitted
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Position of the entrance as a fraction of a full turn; random when omitted
+    #[arg(long, value_parser = parse_turn)]
+    entrance: Option<f64>,
+
+    /// Add an exit at a fraction of a full turn, or at a random position
+    #[arg(long, value_parser = parse_placement)]
+    exit: Option<Placement>,
+
+    /// Where the solution ends: `centre`, `exit` or `<RING>:<TURN>`
+    #[arg(long, default_value = "centre", value_parser = parse_goal)]
+    goal: Goal,
+
+    /// Draw the route from the entrance to the goal
+    #[arg(long)]
+    solution: bool,
+
+    /// Draw or solve a maze saved with `--format json` instead of generating one
+    #[arg(long, conflicts_with_all = [
+        "circles", "inner_slices", "subdivision", "spacing", "inner_radius", "min_distance",
+        "algorithm", "weights", "straight", "braid", "seed", "entrance", "exit", "goal",
+        "solution_percent", "min_dead_ends", "max_dead_ends", "attempts", "count",
+    ])]
+    input: Option<PathBuf>,
+
+    /// File to write the maze to [default: maze.<FORMAT>]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Format of the output file
+    #[arg(short, long, value_enum, default_value_t = Format::Svg)]
+    format: Format,
+
+    /// Handlebars template used for the SVG output
+    #[arg(short, long, default_value = "./assets/maze.template.svg")]
+    template: PathBuf,
+
+    /// Width and height of the PNG output in pixels
+    #[arg(long, default_value_t = 800, value_parser = clap::value_parser!(u32).range(1..))]
+    size: u32,
+
+    /// Width of the walls in pixels for the PNG output, in points for the PDF output
+    #[arg(long, default_value_t = 2., value_parser = parse_line_width)]
+    line_width: f32,
+
+    /// Background of the PNG output: `#rrggbb`, `#rrggbbaa` or `transparent`
+    #[arg(long, default_value = "#ffffff", value_parser = parse_color)]
+    background: [u8; 4],
+
+    /// Page size of the PDF output: `a4`, `letter` or `<WIDTH>x<HEIGHT>` in millimetres
+    #[arg(long, default_value = "a4", value_parser = parse_page_size)]
+    page_size: PageSize,
+
+    /// Margin around and between the mazes in the PDF output in millimetres
+    #[arg(long, default_value_t = 15., value_parser = parse_margin)]
+    margin: f32,
+
+    /// Mazes on every page of the PDF output as `<COLUMNS>x<ROWS>`
+    #[arg(long, default_value = "1x1", value_parser = parse_per_page)]
+    per_page: (u32, u32),
+
+    /// Width of an equally wide ring in the G-code, HPGL and DXF output in millimetres
+    #[arg(long, default_value_t = 10., value_parser = parse_positive)]
+    scale: f64,
+
+    /// Drawing speed in the G-code and HPGL output in millimetres per minute
+    #[arg(long, default_value_t = 1000., value_parser = parse_positive)]
+    feed_rate: f64,
+
+    /// G-code that lifts the pen or switches the tool off
+    #[arg(long, default_value = "G0 Z5")]
+    pen_up: String,
+
+    /// G-code that lowers the pen or switches the tool on
+    #[arg(long, default_value = "G1 Z0")]
+    pen_down: String,
+
+    /// Reorder the G-code and HPGL output to shorten the travel with the pen up
+    #[arg(long)]
+    optimise: bool,
+
+    /// Print how hard the maze is to solve, as a table or as one line of JSON per maze
+    #[arg(long, value_enum)]
+    metrics: Option<MetricsFormat>,
+
+    /// Only accept mazes whose solution passes `<MIN>-<MAX>` percent of the cells
+    #[arg(long, value_parser = parse_percent_range)]
+    solution_percent: Option<(f64, f64)>,
+
+    /// Only accept mazes with at least this many dead ends
+    #[arg(long)]
+    min_dead_ends: Option<usize>,
+
+    /// Only accept mazes with at most this many dead ends
+    #[arg(long)]
+    max_dead_ends: Option<usize>,
+
+    /// Number of seeds to try for every maze before giving up on the difficulty
+    #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u32).range(1..))]
+    attempts: u32,
+
+    /// Number of mazes to write to the PDF output, using consecutive seeds
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    count: u32,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    Svg,
+    Png,
+    Pdf,
+    Gcode,
+    Hpgl,
+    Dxf,
+    Json,
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Svg => "svg",
+            Format::Png => "png",
+            Format::Pdf => "pdf",
+            Format::Gcode => "gcode",
+            Format::Hpgl => "plt",
+            Format::Dxf => "dxf",
+            Format::Json => "json",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum MetricsFormat {
+    Table,
+    Json,
+}
+
+#[derive(Serialize)]
+struct Report {
+    seed: u64,
+    #[serde(flatten)]
+    metrics: Metrics,
+}
+
+fn parse_min_distance(value: &str) -> Result<f64, String> {
+    let min_distance: f64 = value.parse().map_err(|e| format!("{}", e))?;
+    if min_distance.is_finite() && min_distance >= 0. {
+        Ok(min_distance)
+    } else {
+        Err(format!("{} is not a finite, non-negative number", value))
+    }
+}
+
+fn parse_margin(value: &str) -> Result<f32, String> {
+    parse_min_distance(value).map(|margin| margin as f32)
+}
+
+fn parse_line_width(value: &str) -> Result<f32, String> {
+    parse_positive(value).map(|line_width| line_width as f32)
+}
+
+fn parse_positive(value: &str) -> Result<f64, String> {
+    let number: f64 = value.parse().map_err(|e| format!("{}", e))?;
+    if number.is_finite() && number > 0. {
+        Ok(number)
+    } else {
+        Err(format!("{} is not a finite, positive number", value))
+    }
+}
+
+fn parse_color(value: &str) -> Result<[u8; 4], String> {
+    if value == "transparent" {
+        return Ok([0, 0, 0, 0]);
+    }
+
+    let hex = value
+        .strip_prefix('#')
+        .filter(|hex| hex.len() == 6 || hex.len() == 8)
+        .ok_or_else(|| format!("{} is not `#rrggbb`, `#rrggbbaa` or `transparent`", value))?;
+    let mut rgba = [255; 4];
+    for (index, channel) in rgba.iter_mut().enumerate().take(hex.len() / 2) {
+        *channel =
+            u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).map_err(|e| format!("{}", e))?;
+    }
+    Ok(rgba)
+}
+
+fn parse_algorithm(value: &str) -> Result<Algorithm, String> {
+    match value {
+        "random-walk" => Ok(Algorithm::RandomWalk),
+        "recursive-backtracker" => Ok(Algorithm::RecursiveBacktracker),
+        "prim" => Ok(Algorithm::Prim),
+        "kruskal" => Ok(Algorithm::Kruskal),
+        "wilson" => Ok(Algorithm::Wilson),
+        "aldous-broder" => Ok(Algorithm::AldousBroder),
+        "growing-tree" => Ok(Algorithm::GrowingTree),
+        "hunt-and-kill" => Ok(Algorithm::HuntAndKill),
+        _ => Err(format!("{} is not a known algorithm", value)),
+    }
+}
+
+fn parse_subdivision(value: &str) -> Result<Subdivision, String> {
+    match value {
+        "interleaved" => Ok(Subdivision::Interleaved),
+        "constant" => Ok(Subdivision::Constant),
+        "proportional" => Ok(Subdivision::Proportional),
+        _ => match value.split_once(':') {
+            Some(("doubling", every)) => match every.parse() {
+                Ok(every) if every > 0 => Ok(Subdivision::Doubling { every }),
+                _ => Err(format!("{} is not a number of rings above 0", every)),
+            },
+            _ => Err(format!("{} is not a known subdivision", value)),
+        },
+    }
+}
+
+fn parse_spacing(value: &str) -> Result<Spacing, String> {
+    match value {
+        "linear" => Ok(Spacing::Linear),
+        "logarithmic" => Ok(Spacing::Logarithmic),
+        _ => value
+            .split(',')
+            .map(parse_positive)
+            .collect::<Result<_, _>>()
+            .map(Spacing::Custom)
+            .map_err(|_| {
+                format!(
+                    "{} is not `linear`, `logarithmic` or a list of radii",
+                    value
+                )
+            }),
+    }
+}
+
+fn parse_dimensions(value: &str) -> Option<(&str, &str)> {
+    value.split_once('x')
+}
+
+fn parse_page_size(value: &str) -> Result<PageSize, String> {
+    match value {
+        "a4" => Ok(PageSize::A4),
+        "letter" => Ok(PageSize::Letter),
+        _ => {
+            let (width, height) = parse_dimensions(value)
+                .ok_or_else(|| format!("{} is not `a4`, `letter` or `<WIDTH>x<HEIGHT>`", value))?;
+            Ok(PageSize::Custom {
+                width: parse_line_width(width)?,
+                height: parse_line_width(height)?,
+            })
+        }
+    }
+}
+
+fn parse_per_page(value: &str) -> Result<(u32, u32), String> {
+    let (columns, rows) =
+        parse_dimensions(value).ok_or_else(|| format!("{} is not `<COLUMNS>x<ROWS>`", value))?;
+    let columns: u32 = columns.parse().map_err(|e| format!("{}", e))?;
+    let rows: u32 = rows.parse().map_err(|e| format!("{}", e))?;
+    if columns == 0 || rows == 0 {
+        return Err(format!("{} does not fit a single maze", value));
+    }
+    Ok((columns, rows))
+}
+
+fn parse_weight(value: &str) -> Result<f64, String> {
+    let weight: f64 = value.parse().map_err(|e| format!("{}", e))?;
+    if weight.is_finite() && weight >= 0. {
+        Ok(weight)
+    } else {
+        Err(format!("{} is not a weight of 0 or more", value))
+    }
+}
+
+fn parse_weights(value: &str) -> Result<[f64; 4], String> {
+    let weights: Vec<f64> = value
+        .split(',')
+        .map(parse_weight)
+        .collect::<Result<_, _>>()?;
+    let weights: [f64; 4] = weights
+        .try_into()
+        .map_err(|_| format!("{} is not four weights", value))?;
+    if weights.iter().all(|weight| *weight == 0.) {
+        return Err("at least one weight must be more than 0".to_string());
+    }
+    Ok(weights)
+}
+
+fn parse_percent(value: &str) -> Result<f64, String> {
+    let percent: f64 = value.parse().map_err(|e| format!("{}", e))?;
+    if (0. ..=100.).contains(&percent) {
+        Ok(percent)
+    } else {
+        Err(format!("{} is not a percentage from 0 to 100", value))
+    }
+}
+
+fn parse_percent_range(value: &str) -> Result<(f64, f64), String> {
+    let (min, max) = value
+        .split_once('-')
+        .ok_or_else(|| format!("{} is not `<MIN>-<MAX>`", value))?;
+    let min: f64 = min.parse().map_err(|e| format!("{}", e))?;
+    let max: f64 = max.parse().map_err(|e| format!("{}", e))?;
+    if (0. ..=100.).contains(&min) && (min..=100.).contains(&max) {
+        Ok((min, max))
+    } else {
+        Err(format!("{} is not a range of percentages", value))
+    }
+}
+
+fn parse_turn(value: &str) -> Result<f64, String> {
+    let turn: f64 = value.parse().map_err(|e| format!("{}", e))?;
+    if (0. ..1.).contains(&turn) {
+        Ok(turn)
+    } else {
+        Err(format!("{} is not a fraction of a turn in [0, 1)", value))
+    }
+}
+
+fn parse_placement(value: &str) -> Result<Placement, String> {
+    if value == "random" {
+        Ok(Placement::Random)
+    } else {
+        parse_turn(value).map(Placement::At)
+    }
+}
+
+fn parse_goal(value: &str) -> Result<Goal, String> {
+    match value {
+        "centre" => Ok(Goal::Centre),
+        "exit" => Ok(Goal::Exit),
+        _ => {
+            let (ring, turn) = value
+                .split_once(':')
+                .ok_or_else(|| format!("{} is not `centre`, `exit` or `<RING>:<TURN>`", value))?;
+            Ok(Goal::Ring {
+                ring: ring.parse().map_err(|e| format!("{}", e))?,
+                angle: parse_turn(turn)?,
+            })
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    if args.count > 1 && !matches!(args.format, Format::Pdf) {
+        return Err("only the PDF output can hold more than one maze".into());
+    }
+
+    let mut config = MazeConfig::new()
+        .circles(args.circles)
+        .inner_slices(args.inner_slices)
+        .subdivision(args.subdivision)
+        .spacing(args.spacing)
+        .inner_radius(args.inner_radius)
+        .min_distance(args.min_distance)
+        .goal(args.goal)
+        .algorithm(args.algorithm)
+        .braid(args.braid)
+        .bias(Bias {
+            inward: args.weights[0],
+            outward: args.weights[1],
+            clockwise: args.weights[2],
+            counter_clockwise: args.weights[3],
+            straight: args.straight,
+        });
+    if let Some(turn) = args.entrance {
+        config = config.entrance(Placement::At(turn));
+    }
+    if let Some(exit) = args.exit {
+        config = config.exit(exit);
+    }
+
+    let difficulty = Difficulty {
+        solution_percent: args.solution_percent,
+        min_dead_ends: args.min_dead_ends,
+        max_dead_ends: args.max_dead_ends,
+    };
+    let mut seed = args.seed.unwrap_or_else(rand::random);
+    let mut mazes = Vec::new();
+    if let Some(input) = &args.input {
+        mazes.push(Maze::read_json(input)?);
+    }
+    while mazes.len() < args.count as usize {
+        let maze = config
+            .clone()
+            .seed(seed)
+            .build_within(&difficulty, args.attempts)?
+            .ok_or_else(|| {
+                format!(
+                    "no maze within the difficulty in {} attempts from seed {}",
+                    args.attempts, seed
+                )
+            })?;
+        seed = maze.seed().wrapping_add(1);
+        mazes.push(maze);
+    }
+    for maze in &mazes {
+        match args.metrics {
+            None => println!("seed: {}", maze.seed()),
+            Some(MetricsFormat::Table) => println!("seed: {}\n{}", maze.seed(), maze.metrics()),
+            Some(MetricsFormat::Json) => println!(
+                "{}",
+                serde_json::to_string(&Report {
+                    seed: maze.seed(),
+                    metrics: maze.metrics(),
+                })?
+            ),
+        }
+    }
+    let maze = &mazes[0];
+    if args.optimise && matches!(args.format, Format::Gcode | Format::Hpgl) {
+        let order = maze.drawing_order();
+        println!(
+            "pen-up distance: {:.0} mm before, {:.0} mm after optimising",
+            order.pen_up_before() * args.scale,
+            order.pen_up_after() * args.scale
+        );
+    }
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("maze.{}", args.format.extension())));
+    let png_options = PngOptions {
+        size: args.size,
+        line_width: args.line_width,
+        background: args.background,
+        ..PngOptions::default()
+    };
+    let pdf_options = PdfOptions {
+        page_size: args.page_size,
+        margin: args.margin,
+        columns: args.per_page.0,
+        rows: args.per_page.1,
+        line_width: args.line_width,
+    };
+    let plot_options = PlotOptions {
+        scale: args.scale,
+        feed_rate: args.feed_rate,
+        pen_up: args.pen_up,
+        pen_down: args.pen_down,
+        optimise: args.optimise,
+    };
+    match args.format {
+        Format::Svg if args.solution => maze.write_svg_with_solution(&args.template, &output),
+        Format::Svg => maze.write_svg(&args.template, &output),
+        Format::Png if args.solution => maze.write_png_with_solution(&png_options, &output),
+        Format::Png => maze.write_png(&png_options, &output),
+        Format::Pdf => pdf::draw::draw(
+            &mazes.iter().collect::<Vec<_>>(),
+            args.solution,
+            &pdf_options,
+            &output,
+        ),
+        Format::Gcode => maze.write_gcode(&plot_options, &output),
+        Format::Hpgl => maze.write_hpgl(&plot_options, &output),
+        Format::Dxf => maze.write_dxf(&DxfOptions { scale: args.scale }, &output),
+        Format::Json => maze.write_json(&output),
+    }?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod main_test {
+    use circlemaze::{pdf::draw::PageSize, Algorithm, Goal, Placement, Spacing, Subdivision};
+
+    use clap::Parser;
+
+    use super::{
+        parse_algorithm, parse_color, parse_goal, parse_min_distance, parse_page_size,
+        parse_per_page, parse_percent, parse_percent_range, parse_placement, parse_spacing,
+        parse_subdivision, parse_weights, Args,
+    };
+
+    #[test]
+    fn test_input_conflicts_with_generation() {
+        let parse = |flags: &[&str]| {
+            Args::try_parse_from(["circlemaze", "--input", "maze.json"].iter().chain(flags))
+        };
+        assert!(parse(&["--solution", "--format", "pdf"]).is_ok());
+        for flags in [
+            &["--circles", "8"][..],
+            &["--seed", "1"],
+            &["--algorithm", "prim"],
+            &["--braid", "50"],
+            &["--min-distance", "0.5"],
+            &["--goal", "exit"],
+            &["--min-dead-ends", "3"],
+        ] {
+            assert!(parse(flags).is_err(), "{:?}", flags);
+        }
+    }
+
+    #[test]
+    fn test_parse_min_distance() {
+        assert_eq!(Ok(0.3), parse_min_distance("0.3"));
+        assert_eq!(Ok(0.), parse_min_distance("0"));
+        assert!(parse_min_distance("-0.1").is_err());
+        assert!(parse_min_distance("inf").is_err());
+        assert!(parse_min_distance("NaN").is_err());
+        assert!(parse_min_distance("abc").is_err());
+    }
+
+    #[test]
+    fn test_parse_placement() {
+        assert_eq!(Ok(Placement::Random), parse_placement("random"));
+        assert_eq!(Ok(Placement::At(0.25)), parse_placement("0.25"));
+        assert!(parse_placement("1").is_err());
+        assert!(parse_placement("-0.5").is_err());
+    }
+
+    #[test]
+    fn test_parse_goal() {
+        assert_eq!(Ok(Goal::Centre), parse_goal("centre"));
+        assert_eq!(Ok(Goal::Exit), parse_goal("exit"));
+        assert_eq!(
+            Ok(Goal::Ring {
+                ring: 3,
+                angle: 0.5
+            }),
+            parse_goal("3:0.5")
+        );
+        assert!(parse_goal("middle").is_err());
+        assert!(parse_goal("3:2").is_err());
+    }
+
+    #[test]
+    fn test_parse_subdivision() {
+        assert_eq!(Ok(Subdivision::Constant), parse_subdivision("constant"));
+        assert_eq!(
+            Ok(Subdivision::Doubling { every: 3 }),
+            parse_subdivision("doubling:3")
+        );
+        assert!(parse_subdivision("doubling:0").is_err());
+        assert!(parse_subdivision("doubling").is_err());
+        assert!(parse_subdivision("square").is_err());
+    }
+
+    #[test]
+    fn test_parse_spacing() {
+        assert_eq!(Ok(Spacing::Logarithmic), parse_spacing("logarithmic"));
+        assert_eq!(
+            Ok(Spacing::Custom(vec![3., 4.5, 5.])),
+            parse_spacing("3,4.5,5")
+        );
+        assert!(parse_spacing("3,-4").is_err());
+        assert!(parse_spacing("square").is_err());
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(Ok([0, 0, 0, 0]), parse_color("transparent"));
+        assert_eq!(Ok([255, 16, 0, 255]), parse_color("#ff1000"));
+        assert_eq!(Ok([255, 16, 0, 128]), parse_color("#ff100080"));
+        assert!(parse_color("ff1000").is_err());
+        assert!(parse_color("#ff10").is_err());
+        assert!(parse_color("#gg1000").is_err());
+    }
+
+    #[test]
+    fn test_parse_page_size() {
+        assert_eq!(Ok(PageSize::A4), parse_page_size("a4"));
+        assert_eq!(Ok(PageSize::Letter), parse_page_size("letter"));
+        assert_eq!(
+            Ok(PageSize::Custom {
+                width: 100.,
+                height: 150.5
+            }),
+            parse_page_size("100x150.5")
+        );
+        assert!(parse_page_size("a5").is_err());
+        assert!(parse_page_size("0x100").is_err());
+    }
+
+    #[test]
+    fn test_parse_per_page() {
+        assert_eq!(Ok((2, 3)), parse_per_page("2x3"));
+        assert!(parse_per_page("2").is_err());
+        assert!(parse_per_page("0x3").is_err());
+        assert!(parse_per_page("ax3").is_err());
+    }
+
+    #[test]
+    fn test_parse_percent() {
+        assert_eq!(Ok(25.), parse_percent("25"));
+        assert_eq!(Ok(100.), parse_percent("100"));
+        assert!(parse_percent("-1").is_err());
+        assert!(parse_percent("101").is_err());
+        assert!(parse_percent("NaN").is_err());
+    }
+
+    #[test]
+    fn test_parse_percent_range() {
+        assert_eq!(Ok((20., 35.5)), parse_percent_range("20-35.5"));
+        assert_eq!(Ok((0., 100.)), parse_percent_range("0-100"));
+        assert!(parse_percent_range("20").is_err());
+        assert!(parse_percent_range("40-20").is_err());
+        assert!(parse_percent_range("20-120").is_err());
+    }
+
+    #[test]
+    fn test_parse_weights() {
+        assert_eq!(Ok([1., 0.5, 4., 0.]), parse_weights("1,0.5,4,0"));
+        assert!(parse_weights("1,1,1").is_err());
+        assert!(parse_weights("1,1,-1,1").is_err());
+        assert!(parse_weights("0,0,0,0").is_err());
+    }
+
+    #[test]
+    fn test_parse_algorithm() {
+        assert_eq!(Ok(Algorithm::RandomWalk), parse_algorithm("random-walk"));
+        assert_eq!(Ok(Algorithm::HuntAndKill), parse_algorithm("hunt-and-kill"));
+        assert!(parse_algorithm("eller").is_err());
+    }
 }
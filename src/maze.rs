@@ -1,4 +1,15 @@
 pub mod components;
 pub mod maze_builder;
 pub mod circular_grid;
+pub mod config;
+pub mod solver;
 pub mod test_utils;
+pub mod algorithms;
+pub mod graph;
+pub mod metrics;
+pub mod braid;
+pub mod pool;
+#[cfg(test)]
+mod properties;
+pub mod subdivision;
+pub mod radii;
@@ -0,0 +1,470 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    components::{random_nr, CircleCoordinate, Direction, MazeRng},
+    maze_builder::MazeBuilder,
+};
+use crate::{Error, Result};
+
+/// Connects the free coordinates of a [`MazeBuilder`] to the walls that are
+/// already there, starting with the outer circle. Every coordinate joins the
+/// walls exactly once, so the walls form a tree and every cell of the maze can
+/// be reached. Coordinates an algorithm leaves free are connected by random
+/// walks afterwards.
+pub trait MazeAlgorithm {
+    fn grow(&self, builder: &mut MazeBuilder) -> Result<()>;
+}
+
+/// The algorithms to choose from when building a maze.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Algorithm {
+    RandomWalk,
+    RecursiveBacktracker,
+    Prim,
+    Kruskal,
+    Wilson,
+    AldousBroder,
+    GrowingTree,
+    HuntAndKill,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 8] = [
+        Algorithm::RandomWalk,
+        Algorithm::RecursiveBacktracker,
+        Algorithm::Prim,
+        Algorithm::Kruskal,
+        Algorithm::Wilson,
+        Algorithm::AldousBroder,
+        Algorithm::GrowingTree,
+        Algorithm::HuntAndKill,
+    ];
+
+    pub fn maze_algorithm(&self) -> Box<dyn MazeAlgorithm> {
+        match self {
+            Algorithm::RandomWalk => Box::new(RandomWalk),
+            Algorithm::RecursiveBacktracker => Box::new(RecursiveBacktracker),
+            Algorithm::Prim => Box::new(Prim),
+            Algorithm::Kruskal => Box::new(Kruskal),
+            Algorithm::Wilson => Box::new(Wilson),
+            Algorithm::AldousBroder => Box::new(AldousBroder),
+            Algorithm::GrowingTree => Box::new(GrowingTree),
+            Algorithm::HuntAndKill => Box::new(HuntAndKill),
+        }
+    }
+}
+
+/// Walks from random free coordinates, never crossing its own path, until a
+/// wall is reached. Close coordinates are removed while the walls grow.
+pub struct RandomWalk;
+
+impl MazeAlgorithm for RandomWalk {
+    fn grow(&self, builder: &mut MazeBuilder) -> Result<()> {
+        builder.random_walks()
+    }
+}
+
+/// Depth-first search: extends the newest wall until it gets stuck, then
+/// backtracks. Gives long, winding corridors.
+pub struct RecursiveBacktracker;
+
+impl MazeAlgorithm for RecursiveBacktracker {
+    fn grow(&self, builder: &mut MazeBuilder) -> Result<()> {
+        grow_tree(builder, |_rng, active| active - 1);
+        Ok(())
+    }
+}
+
+/// Randomised Prim: extends the walls from a random point on their border.
+/// Gives many short dead ends.
+pub struct Prim;
+
+impl MazeAlgorithm for Prim {
+    fn grow(&self, builder: &mut MazeBuilder) -> Result<()> {
+        builder.remove_close_coords();
+
+        let mut frontier: Vec<(CircleCoordinate, Direction)> = Vec::new();
+        for coord in builder.free() {
+            for (neighbour, direction) in builder.neighbours(coord) {
+                if !builder.is_free(&neighbour) {
+                    frontier.push((neighbour, direction.opposite()));
+                }
+            }
+        }
+
+        while !frontier.is_empty() {
+            let (from, direction) = frontier.swap_remove(random_nr(builder.rng(), frontier.len()));
+            if !free_in_direction(builder, &from, direction) {
+                continue;
+            }
+            if let Some(to) = builder.connect(&from, direction) {
+                frontier.extend(
+                    free_neighbours(builder, &to)
+                        .into_iter()
+                        .map(|(_, d)| (to.clone(), d)),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Randomised Kruskal: joins neighbouring coordinates in random order as long
+/// as that does not close a loop.
+pub struct Kruskal;
+
+impl MazeAlgorithm for Kruskal {
+    fn grow(&self, builder: &mut MazeBuilder) -> Result<()> {
+        builder.remove_close_coords();
+
+        // Set 0 holds everything that is part of the walls already.
+        let free: Vec<CircleCoordinate> = builder.free().cloned().collect();
+        let sets: BTreeMap<CircleCoordinate, usize> = free
+            .iter()
+            .enumerate()
+            .map(|(index, coord)| (coord.clone(), index + 1))
+            .collect();
+        let mut parents: Vec<usize> = (0..=free.len()).collect();
+
+        let mut edges: Vec<(CircleCoordinate, Direction)> = Vec::new();
+        for coord in &free {
+            for (neighbour, direction) in builder.neighbours(coord) {
+                if !sets.contains_key(&neighbour) || coord < &neighbour {
+                    edges.push((coord.clone(), direction));
+                }
+            }
+        }
+
+        while !edges.is_empty() {
+            let (from, direction) = edges.swap_remove(random_nr(builder.rng(), edges.len()));
+            let Some((to, _)) = builder
+                .neighbours(&from)
+                .into_iter()
+                .find(|(_, d)| *d == direction)
+            else {
+                continue;
+            };
+
+            let set = |coord: &CircleCoordinate| sets.get(coord).copied().unwrap_or(0);
+            let (root_from, root_to) = (
+                find_root(&mut parents, set(&from)),
+                find_root(&mut parents, set(&to)),
+            );
+            if root_from != root_to {
+                parents[root_from.max(root_to)] = root_from.min(root_to);
+                builder.connect(&from, direction);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn find_root(parents: &mut [usize], set: usize) -> usize {
+    let mut root = set;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    parents[set] = root;
+    root
+}
+
+/// Wilson: loop-erased random walks from free coordinates until the walls are
+/// reached. Every possible maze is equally likely.
+pub struct Wilson;
+
+impl MazeAlgorithm for Wilson {
+    fn grow(&self, builder: &mut MazeBuilder) -> Result<()> {
+        builder.remove_close_coords();
+
+        while builder.free_len() > 0 {
+            let count = builder.free_len();
+            let n = random_nr(builder.rng(), count);
+            let Some(first) = builder.nth_free(n) else {
+                break;
+            };
+
+            // Where every coordinate lies on the path, to erase loops at once.
+            let mut on_path: HashMap<CircleCoordinate, usize> = HashMap::from([(first.clone(), 0)]);
+            let mut path = vec![first];
+            let mut steps: Vec<Direction> = Vec::new();
+            loop {
+                let current = &path[path.len() - 1];
+                let (next, direction) = random_neighbour(builder, current)?;
+                steps.push(direction);
+                if !builder.is_free(&next) {
+                    path.push(next);
+                    break;
+                }
+                if let Some(&index) = on_path.get(&next) {
+                    for erased in path.drain(index + 1..) {
+                        on_path.remove(&erased);
+                    }
+                    steps.truncate(index);
+                } else {
+                    on_path.insert(next.clone(), path.len());
+                    path.push(next);
+                }
+            }
+
+            // Grow backwards from the wall that was reached.
+            for index in (0..steps.len()).rev() {
+                builder.connect(&path[index + 1], steps[index].opposite());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Aldous-Broder: a random walk over the whole grid that connects every free
+/// coordinate it steps on. Every possible maze is equally likely. The walk
+/// takes long to find the last free coordinates, so it is limited to mazes of
+/// at most [`AldousBroder::MAX_CELLS`] cells.
+pub struct AldousBroder;
+
+impl AldousBroder {
+    /// A few seconds of walking; twice as many circles take about ten times as long.
+    pub const MAX_CELLS: u64 = 20_000;
+}
+
+impl MazeAlgorithm for AldousBroder {
+    fn grow(&self, builder: &mut MazeBuilder) -> Result<()> {
+        builder.remove_close_coords();
+
+        let Some(mut current) = start(builder) else {
+            return Ok(());
+        };
+        let mut remaining = builder.free_len();
+        while remaining > 0 {
+            let (next, direction) = random_neighbour(builder, &current)?;
+            if builder.is_free(&next) {
+                builder.connect(&current, direction);
+                remaining -= 1;
+            }
+            current = next;
+        }
+        Ok(())
+    }
+}
+
+/// Growing tree: extends the newest wall half of the time and a random one
+/// otherwise, mixing the long corridors of the recursive backtracker with the
+/// short dead ends of Prim.
+pub struct GrowingTree;
+
+impl MazeAlgorithm for GrowingTree {
+    fn grow(&self, builder: &mut MazeBuilder) -> Result<()> {
+        grow_tree(builder, |rng, active| {
+            if rng.gen_bool(0.5) {
+                active - 1
+            } else {
+                random_nr(rng, active)
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Hunt-and-kill: walks randomly until stuck, then hunts for a free coordinate
+/// next to a wall. Every hunt goes outward from where the one before stopped,
+/// and starts over at the centre once it has passed the outer circle.
+pub struct HuntAndKill;
+
+impl MazeAlgorithm for HuntAndKill {
+    fn grow(&self, builder: &mut MazeBuilder) -> Result<()> {
+        builder.remove_close_coords();
+
+        let mut current = start(builder);
+        let mut last = None;
+        let mut cursor = None;
+        while let Some(coord) = current {
+            let options = free_neighbours(builder, &coord);
+            current = if options.is_empty() {
+                last = None;
+                hunt(builder, &mut cursor)
+            } else {
+                let (_, direction) = options[choose(builder, &options, last)];
+                last = Some(direction);
+                builder.connect(&coord, direction)
+            };
+        }
+        Ok(())
+    }
+}
+
+/// Looks for a free coordinate next to a wall from `cursor` on, and moves the
+/// cursor to the one it connects.
+fn hunt(
+    builder: &mut MazeBuilder,
+    cursor: &mut Option<CircleCoordinate>,
+) -> Option<CircleCoordinate> {
+    let count = builder.free_len();
+    let first = cursor
+        .as_ref()
+        .map_or(0, |coord| builder.free_before(coord));
+    for n in (first..count).chain(0..first) {
+        let coord = builder.nth_free(n)?;
+        let taken = builder
+            .neighbours(&coord)
+            .into_iter()
+            .find(|(neighbour, _)| !builder.is_free(neighbour));
+        if let Some((neighbour, direction)) = taken {
+            *cursor = Some(coord);
+            return builder.connect(&neighbour, direction.opposite());
+        }
+    }
+    None
+}
+
+/// Keeps a list of active walls and extends the one picked by `select` until
+/// it cannot grow any further.
+fn grow_tree(builder: &mut MazeBuilder, mut select: impl FnMut(&mut MazeRng, usize) -> usize) {
+    builder.remove_close_coords();
+
+    while let Some(first) = start(builder) {
+        // Every active coordinate with the direction of the step that reached it.
+        let mut active = vec![(first, None)];
+        while !active.is_empty() {
+            let index = select(builder.rng(), active.len());
+            let (from, last) = active[index].clone();
+            let options = free_neighbours(builder, &from);
+            if options.is_empty() {
+                active.remove(index);
+                continue;
+            }
+
+            let (_, direction) = options[choose(builder, &options, last)];
+            if let Some(to) = builder.connect(&from, direction) {
+                active.push((to, Some(direction)));
+            }
+        }
+    }
+}
+
+/// A random coordinate on the walls next to a free coordinate.
+fn start(builder: &mut MazeBuilder) -> Option<CircleCoordinate> {
+    let mut options: Vec<CircleCoordinate> = Vec::new();
+    for coord in builder.free() {
+        for (neighbour, _) in builder.neighbours(coord) {
+            if !builder.is_free(&neighbour) {
+                options.push(neighbour);
+            }
+        }
+    }
+
+    if options.is_empty() {
+        None
+    } else {
+        Some(options.swap_remove(random_nr(builder.rng(), options.len())))
+    }
+}
+
+fn free_neighbours(
+    builder: &MazeBuilder,
+    coord: &CircleCoordinate,
+) -> Vec<(CircleCoordinate, Direction)> {
+    builder
+        .neighbours(coord)
+        .into_iter()
+        .filter(|(neighbour, _)| builder.is_free(neighbour))
+        .collect()
+}
+
+fn choose(
+    builder: &mut MazeBuilder,
+    options: &[(CircleCoordinate, Direction)],
+    last: Option<Direction>,
+) -> usize {
+    let directions: Vec<Direction> = options.iter().map(|(_, direction)| *direction).collect();
+    builder.choose(&directions, last)
+}
+
+fn free_in_direction(builder: &MazeBuilder, from: &CircleCoordinate, direction: Direction) -> bool {
+    builder
+        .neighbours(from)
+        .iter()
+        .any(|(neighbour, d)| *d == direction && builder.is_free(neighbour))
+}
+
+fn random_neighbour(
+    builder: &mut MazeBuilder,
+    coord: &CircleCoordinate,
+) -> Result<(CircleCoordinate, Direction)> {
+    let options = Direction::ALL.map(|direction| {
+        builder
+            .neighbour(coord, direction)
+            .map(|neighbour| (neighbour, direction))
+    });
+    let count = options.iter().flatten().count();
+    if count == 0 {
+        return Err(Error::Generation(format!("{} has no neighbours", coord)));
+    }
+    let index = random_nr(builder.rng(), count);
+    Ok(options.into_iter().flatten().nth(index).unwrap())
+}
+
+#[cfg(test)]
+mod algorithms_test {
+    use crate::{Goal, MazeConfig};
+
+    use super::Algorithm;
+
+    #[test]
+    fn test_same_seed_same_maze() {
+        for algorithm in Algorithm::ALL {
+            let config = MazeConfig::new().seed(8).algorithm(algorithm);
+            assert_eq!(
+                config.build().unwrap().borders(),
+                config.build().unwrap().borders(),
+                "{:?}",
+                algorithm
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_algorithm_is_solvable() {
+        for algorithm in Algorithm::ALL {
+            for seed in 0..4 {
+                let maze = MazeConfig::new()
+                    .seed(seed)
+                    .circles(6)
+                    .algorithm(algorithm)
+                    .build()
+                    .unwrap();
+                assert!(
+                    maze.solution().is_some(),
+                    "{:?} seed {} not solvable",
+                    algorithm,
+                    seed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_cell_reachable() {
+        for algorithm in Algorithm::ALL {
+            let config = MazeConfig::new().seed(3).circles(6).algorithm(algorithm);
+            for ring in [0, 2, 4] {
+                for angle in [0.05, 0.55] {
+                    let maze = config
+                        .clone()
+                        .goal(Goal::Ring { ring, angle })
+                        .build()
+                        .unwrap();
+                    assert!(
+                        maze.solution().is_some(),
+                        "{:?} cannot reach {}:{}",
+                        algorithm,
+                        ring,
+                        angle
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use rand::seq::SliceRandom;
+
+use super::{
+    components::{random_nr, MazeRng},
+    graph::{CellId, MazeGraph, Passage},
+};
+
+/// Removes `percent` of the dead ends by opening one of their closed passages,
+/// which adds loops to the maze. Passages to other dead ends are opened first,
+/// so that a single passage removes two dead ends where possible. The outer
+/// circle is never opened.
+pub fn braid(graph: &mut MazeGraph, percent: f64, rng: &mut MazeRng) {
+    let mut dead_ends: Vec<CellId> = (0..graph.cells().len())
+        .filter(|cell| is_dead_end(graph, *cell))
+        .collect();
+    dead_ends.shuffle(rng);
+    let count = (dead_ends.len() as f64 * percent.clamp(0., 100.) / 100.).round() as usize;
+
+    let mut removed = 0;
+    for cell in dead_ends {
+        if removed >= count {
+            break;
+        }
+        // An earlier passage may have opened this dead end already.
+        if !is_dead_end(graph, cell) {
+            continue;
+        }
+
+        let closed: Vec<Passage> = graph
+            .neighbours(cell)
+            .iter()
+            .filter(|passage| {
+                !passage.open && passage.cell != cell && !graph.is_outside(passage.cell)
+            })
+            .cloned()
+            .collect();
+        let dead_end_neighbours: Vec<Passage> = closed
+            .iter()
+            .filter(|passage| is_dead_end(graph, passage.cell))
+            .cloned()
+            .collect();
+        let options = if dead_end_neighbours.is_empty() {
+            closed
+        } else {
+            dead_end_neighbours
+        };
+        if options.is_empty() {
+            continue;
+        }
+
+        let passage = &options[random_nr(rng, options.len())];
+        if is_dead_end(graph, passage.cell) {
+            removed += 1;
+        }
+        graph.set_open(cell, passage.cell, &passage.angle, true);
+        removed += 1;
+    }
+}
+
+fn is_dead_end(graph: &MazeGraph, cell: CellId) -> bool {
+    !graph.is_outside(cell) && graph.degree(cell) == 1
+}
+
+#[cfg(test)]
+mod braid_test {
+    use crate::MazeConfig;
+
+    #[test]
+    fn test_no_braid() {
+        let config = MazeConfig::new().seed(5);
+        assert_eq!(
+            config.build().unwrap().borders(),
+            config.clone().braid(0.).build().unwrap().borders()
+        );
+    }
+
+    #[test]
+    fn test_braid() {
+        for seed in 0..3 {
+            let config = MazeConfig::new().seed(seed).circles(7);
+            let perfect = config.build().unwrap().metrics();
+            let half = config.clone().braid(50.).build().unwrap();
+            let full = config.clone().braid(100.).build().unwrap();
+
+            let dead_ends = half.metrics().dead_ends as f64;
+            assert!((dead_ends - perfect.dead_ends as f64 / 2.).abs() <= 2.);
+            assert!(full.metrics().dead_ends <= perfect.dead_ends / 10);
+            assert!(full.metrics().junctions > perfect.junctions);
+            assert!(half.solution().is_some());
+            assert!(full.solution().is_some());
+        }
+    }
+
+    #[test]
+    fn test_braided_borders_are_merged() {
+        let maze = MazeConfig::new().seed(8).braid(60.).build().unwrap();
+        let graph = maze.graph();
+        assert_eq!(maze.borders(), graph.to_borders());
+        // Walls of the same kind that meet would have been merged into one.
+        for (index, border) in maze.borders().iter().enumerate() {
+            for other in &maze.borders()[index + 1..] {
+                if border.border_type() == other.border_type() {
+                    assert_ne!(border.end, other.start);
+                    assert_ne!(other.end, border.start);
+                }
+            }
+        }
+    }
+}
@@ -1,19 +1,81 @@
-use std::cmp::min;
+use std::{
+    cmp::min,
+    collections::{HashMap, HashSet},
+};
 
-use fraction::{ToPrimitive, Zero};
+use fraction::{Integer, ToPrimitive, Zero};
 
 use super::components::{
-    random_nr, Angle, CellState, CircleCoordinate, Direction, Distributor, Grid,
+    random_nr, Angle, AngleInt, CellState, CircleCoordinate, Direction, Distributor, Grid, MazeRng,
 };
+use super::pool::Pool;
+use super::subdivision::{Interleaved, RingSubdivision};
 
 pub fn build(outer_circle: u32, inner_slices: u32, min_dist: f64) -> Box<dyn Grid> {
-    Box::new(build_circular_grid(outer_circle, inner_slices, min_dist))
+    build_subdivided(outer_circle, inner_slices, Box::new(Interleaved), min_dist)
+}
+
+/// Builds a grid whose rings are divided into cells by `subdivision`.
+pub fn build_subdivided(
+    outer_circle: u32,
+    inner_slices: u32,
+    subdivision: Box<dyn RingSubdivision>,
+    min_dist: f64,
+) -> Box<dyn Grid> {
+    Box::new(build_circular_grid(
+        outer_circle,
+        inner_slices,
+        subdivision,
+        min_dist,
+    ))
+}
+
+/// Whether the angles of a grid fit [`AngleInt`]. Arithmetic between
+/// neighbouring circles needs room for the product of two of their
+/// denominators.
+pub fn fits(outer_circle: u32, inner_slices: u32, subdivision: &dyn RingSubdivision) -> bool {
+    (0..=outer_circle).all(|circle| {
+        denominator(circle, inner_slices, subdivision)
+            .and_then(|denom| denom.checked_mul(denom))
+            .is_some()
+    })
+}
+
+/// The number of cells in the rings on both sides of a circle. The innermost
+/// circle has no ring inside it.
+fn rings_beside(
+    circle: u32,
+    inner_slices: u32,
+    subdivision: &dyn RingSubdivision,
+) -> Option<(AngleInt, AngleInt)> {
+    let outside = subdivision.cells(circle, inner_slices)?;
+    let inside = match circle {
+        0 => outside,
+        _ => subdivision.cells(circle - 1, inner_slices)?,
+    };
+    Some((AngleInt::from(inside), AngleInt::from(outside))).filter(|&(i, o)| i > 0 && o > 0)
+}
+
+/// The common denominator of all grid angles on a circle.
+fn denominator(
+    circle: u32,
+    inner_slices: u32,
+    subdivision: &dyn RingSubdivision,
+) -> Option<AngleInt> {
+    let (inside, outside) = rings_beside(circle, inner_slices, subdivision)?;
+    (inside / inside.gcd(&outside)).checked_mul(outside)
 }
 
-fn build_circular_grid(outer_circle: u32, inner_slices: u32, min_dist: f64) -> CircularGrid {
+fn build_circular_grid(
+    outer_circle: u32,
+    inner_slices: u32,
+    subdivision: Box<dyn RingSubdivision>,
+    min_dist: f64,
+) -> CircularGrid {
     CircularGrid {
         outer_circle,
         inner_slices,
+        subdivision,
         min_dist,
         coords: Vec::new(),
     }
@@ -22,29 +84,26 @@ fn build_circular_grid(outer_circle: u32, inner_slices: u32, min_dist: f64) -> C
 struct CircularGrid {
     outer_circle: u32,
     inner_slices: u32,
+    subdivision: Box<dyn RingSubdivision>,
     min_dist: f64,
     coords: Vec<Vec<CircleCoordinate>>,
 }
 
-type OptionSelector = dyn Fn(Vec<&CircleCoordinate>) -> CircleCoordinate;
-
-fn random_option(options: Vec<&CircleCoordinate>) -> CircleCoordinate {
-    options[random_nr(options.len())].to_owned()
-}
+/// Picks the index of one of the given number of options.
+type OptionSelector = dyn FnMut(usize) -> usize;
 
 impl CircularGrid {
-    fn circular_dist(&mut self) -> CircularDist {
-        self.circular_dist_with_selector(Box::new(random_option))
+    fn circular_dist(&mut self, mut rng: MazeRng) -> CircularDist {
+        self.circular_dist_with_selector(Box::new(move |options| random_nr(&mut rng, options)))
     }
 
     fn circular_dist_with_selector(&mut self, selector: Box<OptionSelector>) -> CircularDist {
-        for circle in 0..=self.outer_circle {
-            self.coords.push(self.coords_on_circle(circle));
-        }
+        self.coords = self.circles();
 
         CircularDist {
             coords: self.coords.clone(),
-            taken: Vec::new(),
+            taken: HashSet::new(),
+            free: FreePool::new(self.coords.iter().flatten().cloned().collect()),
             selector,
             inner_slices: self.inner_slices,
             min_dist: self.min_dist,
@@ -53,8 +112,14 @@ impl CircularGrid {
 }
 
 impl Grid for CircularGrid {
-    fn dist(&mut self) -> Box<dyn Distributor> {
-        Box::new(self.circular_dist())
+    fn dist(&mut self, rng: MazeRng) -> Box<dyn Distributor> {
+        Box::new(self.circular_dist(rng))
+    }
+
+    fn circles(&self) -> Vec<Vec<CircleCoordinate>> {
+        (0..=self.outer_circle)
+            .map(|circle| self.coords_on_circle(circle))
+            .collect()
     }
 }
 
@@ -78,28 +143,30 @@ impl CircularGrid {
         result
     }
 
+    /// The next point clockwise on the circle, where a cell of the ring inside
+    /// or outside of it starts.
     fn next_coord_on_circle(&self, coord: &CircleCoordinate) -> CircleCoordinate {
-        if coord.circle == 0 {
+        // An angle that is not a fraction, or a circle too large for the
+        // angles, wraps around, which ends the circle.
+        let next = || {
+            let subdivision = &*self.subdivision;
+            let (n, d) = (coord.angle.numer()?, coord.angle.denom()?);
+            let (inside, outside) = rings_beside(coord.circle, self.inner_slices, subdivision)?;
+            let normalized_denom = denominator(coord.circle, self.inner_slices, subdivision)?;
+            let normalized_numer = n.checked_mul(normalized_denom / d)?;
+
+            // The width of a cell in each ring, in steps of the denominator.
+            let (inner, outer) = (normalized_denom / inside, normalized_denom / outside);
+            let diff1 = inner - (normalized_numer % inner);
+            let diff2 = outer - (normalized_numer % outer);
+            Some((normalized_numer + min(diff1, diff2), normalized_denom))
+        };
+        let Some((next_numer, normalized_denom)) = next() else {
             return CircleCoordinate {
-                circle: 0,
-                angle: if coord.angle == Angle::new(self.inner_slices - 1, self.inner_slices) {
-                    Angle::from(0)
-                } else {
-                    coord.angle + Angle::new(1_u32, self.inner_slices)
-                },
+                circle: coord.circle,
+                angle: Angle::from(0),
             };
-        }
-
-        let n = coord.angle.numer().unwrap();
-        let d = coord.angle.denom().unwrap();
-
-        let normalized_denom = coord.circle * (coord.circle + 1) * self.inner_slices;
-        let normalized_numer = n * (normalized_denom / d);
-
-        let diff1 = coord.circle - (normalized_numer % coord.circle);
-        let diff2 = (coord.circle + 1) - (normalized_numer % (coord.circle + 1));
-
-        let next_numer = normalized_numer + min(diff1, diff2);
+        };
 
         CircleCoordinate {
             circle: coord.circle,
@@ -114,7 +181,9 @@ impl CircularGrid {
 
 pub struct CircularDist {
     coords: Vec<Vec<CircleCoordinate>>,
-    taken: Vec<CircleCoordinate>,
+    taken: HashSet<CircleCoordinate>,
+    /// The coordinates that are still in `coords` but not taken.
+    free: FreePool,
     selector: Box<OptionSelector>,
     inner_slices: u32,
     min_dist: f64,
@@ -124,32 +193,41 @@ impl CircularDist {
     fn remove_close_neighbours(&mut self, coord: &CircleCoordinate) {
         let circle = coord.circle;
 
-        let index1 = neighbour_clockwise(&self.coords, coord).unwrap();
+        let Some(index1) = neighbour_clockwise(&self.coords, coord) else {
+            return;
+        };
         let n1 = &self.coords[circle as usize][index1];
         if self.too_close(&coord.angle, &n1.angle, circle) {
-            self.coords[circle as usize].remove(index1);
+            let removed = self.coords[circle as usize].remove(index1);
+            self.free.remove(&removed);
         }
 
-        let index2 = neighbour_counter_clockwise(&self.coords, coord).unwrap();
+        let Some(index2) = neighbour_counter_clockwise(&self.coords, coord) else {
+            return;
+        };
         let n2 = &self.coords[circle as usize][index2];
         if self.too_close(&coord.angle, &n2.angle, circle) {
-            self.coords[circle as usize].remove(index2);
+            let removed = self.coords[circle as usize].remove(index2);
+            self.free.remove(&removed);
         }
     }
 
     fn too_close(&self, a1: &Angle, a2: &Angle, circle: u32) -> bool {
-        let dist = (a1 - a2).abs() * ((circle + 1) * self.inner_slices);
-        dist.to_f64().unwrap() < self.min_dist
+        let dist = (a1 - a2).abs() * self.slices_on(circle);
+        dist.to_f64().is_some_and(|dist| dist < self.min_dist)
+    }
+
+    /// Scales an angle on the circle to a distance, in which the innermost
+    /// slices are 1 wide.
+    fn slices_on(&self, circle: u32) -> AngleInt {
+        (AngleInt::from(circle) + 1) * AngleInt::from(self.inner_slices)
     }
 
     fn take(&mut self, coord: &CircleCoordinate) -> CellState {
-        let state = if self.taken.contains(coord) {
-            CellState::Taken
-        } else {
-            CellState::Free
-        };
+        let state = self.state(coord);
         if state == CellState::Free {
-            self.taken.push(coord.to_owned());
+            self.taken.insert(coord.to_owned());
+            self.free.remove(coord);
         }
         self.remove_close_neighbours(coord);
 
@@ -159,39 +237,121 @@ impl CircularDist {
 
 impl Distributor for CircularDist {
     fn take_from_outer_circle(&mut self) -> (CircleCoordinate, CellState) {
-        let options: Vec<&CircleCoordinate> = self.coords[self.coords.len() - 1].iter().collect();
-        let coord = (self.selector)(options);
+        let outer_coords = &self.coords[self.coords.len() - 1];
+        let coord = outer_coords[(self.selector)(outer_coords.len())].to_owned();
+        let state = self.take(&coord);
+        (coord, state)
+    }
+
+    fn take_from_outer_circle_at(&mut self, angle: &Angle) -> (CircleCoordinate, CellState) {
+        let outer_coords = &self.coords[self.coords.len() - 1];
+        let index = outer_coords.partition_point(|c| &c.angle <= angle);
+        let coord = outer_coords[index.max(1) - 1].to_owned();
         let state = self.take(&coord);
         (coord, state)
     }
 
     fn consume_outer_circle(&mut self) {
-        let outer_coords: Vec<CircleCoordinate> = self.coords[self.coords.len() - 1].clone();
-        self.taken.extend(outer_coords);
+        for coord in &self.coords[self.coords.len() - 1] {
+            self.free.remove(coord);
+            self.taken.insert(coord.to_owned());
+        }
     }
 
     fn take_free(&mut self) -> Option<CircleCoordinate> {
-        let options: Vec<&CircleCoordinate> = self
-            .coords
-            .iter()
-            .flatten()
-            .filter(|c| !self.taken.contains(c))
-            .collect();
-
-        if options.is_empty() {
-            None
+        if self.free.len() == 0 {
+            return None;
+        }
+        let coord = self.free.nth((self.selector)(self.free.len()))?.to_owned();
+        self.take(&coord);
+        Some(coord)
+    }
+
+    fn free(&self) -> Box<dyn Iterator<Item = &CircleCoordinate> + '_> {
+        Box::new(self.free.iter())
+    }
+
+    fn free_len(&self) -> usize {
+        self.free.len()
+    }
+
+    fn nth_free(&self, n: usize) -> Option<CircleCoordinate> {
+        self.free.nth(n).cloned()
+    }
+
+    fn free_before(&self, coord: &CircleCoordinate) -> usize {
+        self.free.rank(coord)
+    }
+
+    fn state(&self, coord: &CircleCoordinate) -> CellState {
+        if self.taken.contains(coord) {
+            CellState::Taken
         } else {
-            let coord = (self.selector)(options);
-            self.take(&coord);
-            Some(coord)
+            CellState::Free
         }
     }
 
-    fn take_neighbour(
-        &mut self,
+    fn remove_close_coords(&mut self) {
+        for circle in 0..self.coords.len() {
+            let coords = std::mem::take(&mut self.coords[circle]);
+            let mut kept: Vec<CircleCoordinate> = Vec::new();
+            for coord in coords.iter().cloned() {
+                match kept.last() {
+                    Some(last) if self.too_close(&last.angle, &coord.angle, circle as u32) => {
+                        if self.state(&coord) == CellState::Taken
+                            && self.state(last) == CellState::Free
+                        {
+                            kept.pop();
+                            kept.push(coord);
+                        } else if self.state(&coord) == CellState::Taken {
+                            kept.push(coord);
+                        }
+                    }
+                    _ => kept.push(coord),
+                }
+            }
+
+            if kept.len() > 1 {
+                let (first, last) = (&kept[0], &kept[kept.len() - 1]);
+                let wrapped = Angle::from(1) - last.angle + first.angle;
+                if (wrapped * self.slices_on(circle as u32))
+                    .to_f64()
+                    .is_some_and(|dist| dist < self.min_dist)
+                {
+                    if self.state(last) == CellState::Free {
+                        kept.pop();
+                    } else if self.state(first) == CellState::Free {
+                        kept.remove(0);
+                    }
+                }
+            }
+            for coord in &coords {
+                if kept.binary_search(coord).is_err() {
+                    self.free.remove(coord);
+                }
+            }
+            self.coords[circle] = kept;
+        }
+    }
+
+    fn coords(&self) -> Vec<Vec<CircleCoordinate>> {
+        // Taking a coordinate removes its close neighbours even when walls
+        // already end there, and those stay corners of cells.
+        let mut coords = self.coords.clone();
+        for coord in &self.taken {
+            let circle = &mut coords[coord.circle as usize];
+            if let Err(index) = circle.binary_search(coord) {
+                circle.insert(index, coord.to_owned());
+            }
+        }
+        coords
+    }
+
+    fn neighbour(
+        &self,
         coord: &CircleCoordinate,
         direction: &Direction,
-    ) -> Option<(CircleCoordinate, CellState)> {
+    ) -> Option<CircleCoordinate> {
         let (circle_index_diff, neigbour_index_option): (isize, Option<usize>) = match direction {
             Direction::Out => (1, neighbour_out(&self.coords, coord)),
             Direction::In => (-1, neigbour_in(&self.coords, coord)),
@@ -199,17 +359,68 @@ impl Distributor for CircularDist {
             Direction::CounterClockwise => (0, neighbour_counter_clockwise(&self.coords, coord)),
         };
 
-        if let Some(index_on_circle) = neigbour_index_option {
-            let circle_index = (coord.circle as usize)
-                .checked_add_signed(circle_index_diff)
-                .unwrap();
-            let coord = self.coords[circle_index][index_on_circle].to_owned();
-            let state = self.take(&coord);
-            Some((coord, state))
-        } else {
-            None
+        neigbour_index_option.and_then(|index_on_circle| {
+            let circle_index = (coord.circle as usize).checked_add_signed(circle_index_diff)?;
+            Some(self.coords[circle_index][index_on_circle].to_owned())
+        })
+    }
+
+    fn take_neighbour(
+        &mut self,
+        coord: &CircleCoordinate,
+        direction: &Direction,
+    ) -> Option<(CircleCoordinate, CellState)> {
+        let neighbour = self.neighbour(coord, direction)?;
+        let state = self.take(&neighbour);
+        Some((neighbour, state))
+    }
+}
+
+/// The free coordinates of a grid in their order, so that picking the n-th one
+/// gives the same mazes for a seed as picking from a list of them.
+struct FreePool {
+    pool: Pool<CircleCoordinate>,
+    slots: HashMap<CircleCoordinate, usize>,
+}
+
+impl FreePool {
+    /// A pool in which all of the ordered `coords` are free.
+    fn new(coords: Vec<CircleCoordinate>) -> FreePool {
+        FreePool {
+            slots: coords
+                .iter()
+                .enumerate()
+                .map(|(slot, coord)| (coord.clone(), slot))
+                .collect(),
+            pool: Pool::from(coords),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    fn remove(&mut self, coord: &CircleCoordinate) {
+        if let Some(&slot) = self.slots.get(coord) {
+            self.pool.remove(slot);
         }
     }
+
+    /// The number of free coordinates before `coord`.
+    fn rank(&self, coord: &CircleCoordinate) -> usize {
+        self.slots
+            .get(coord)
+            .map_or(0, |&slot| self.pool.rank(slot))
+    }
+
+    /// The free coordinate with `n` free coordinates before it.
+    fn nth(&self, n: usize) -> Option<&CircleCoordinate> {
+        self.pool.nth(n).and_then(|slot| self.pool.get(slot))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &CircleCoordinate> {
+        self.pool.iter()
+    }
 }
 
 fn find(coords: &[Vec<CircleCoordinate>], circle: usize, angle: &Angle) -> Option<usize> {
@@ -283,12 +494,25 @@ fn neighbour_counter_clockwise(
 
 #[cfg(test)]
 mod circular_grid_test {
+    use rand::SeedableRng;
+
     use crate::maze::{
-        circular_grid::build_circular_grid,
-        components::{Angle, CellState, CircleCoordinate, Direction, Distributor},
+        circular_grid::{self, fits, CircularGrid},
+        components::{Angle, CellState, CircleCoordinate, Direction, Distributor, MazeRng},
+        graph::clockwise_distance,
+        subdivision::{Constant, Doubling, Interleaved, Proportional, RingSubdivision},
         test_utils::helper_fns::create_coord,
     };
 
+    fn build_circular_grid(outer_circle: u32, inner_slices: u32, min_dist: f64) -> CircularGrid {
+        circular_grid::build_circular_grid(
+            outer_circle,
+            inner_slices,
+            Box::new(Interleaved),
+            min_dist,
+        )
+    }
+
     fn pair(
         circle1: u32,
         numer1: u32,
@@ -312,7 +536,7 @@ mod circular_grid_test {
     #[test]
     fn test_remove_close_neighbours_neighbour_on_max_dist() {
         let mut grid = build_circular_grid(5, 7, 0.5);
-        let mut dist = grid.circular_dist();
+        let mut dist = grid.circular_dist(MazeRng::seed_from_u64(0));
         dist.remove_close_neighbours(&create_coord(5, 0, 1));
         assert_eq!(grid.coords[5], dist.coords[5]);
     }
@@ -320,7 +544,7 @@ mod circular_grid_test {
     #[test]
     fn test_remove_close_neighbours_one_to_remove_1_41_1_35() {
         let mut grid = build_circular_grid(5, 7, 0.5);
-        let mut dist = grid.circular_dist();
+        let mut dist = grid.circular_dist(MazeRng::seed_from_u64(0));
         dist.remove_close_neighbours(&create_coord(5, 1, 42));
         assert_eq!(69, dist.coords[5].len());
         assert!(!dist.coords[5].contains(&create_coord(5, 1, 35)));
@@ -329,7 +553,7 @@ mod circular_grid_test {
     #[test]
     fn test_remove_close_neighbours_max_dist_small_enough() {
         let mut grid = build_circular_grid(5, 7, 0.1);
-        let mut dist = grid.circular_dist();
+        let mut dist = grid.circular_dist(MazeRng::seed_from_u64(0));
         dist.remove_close_neighbours(&create_coord(5, 1, 42));
         assert_eq!(grid.coords[5], dist.coords[5]);
     }
@@ -337,7 +561,7 @@ mod circular_grid_test {
     #[test]
     fn test_remove_close_neighbours_two_to_remove() {
         let mut grid = build_circular_grid(5, 7, 0.7);
-        let mut dist = grid.circular_dist();
+        let mut dist = grid.circular_dist(MazeRng::seed_from_u64(0));
         dist.remove_close_neighbours(&create_coord(5, 3, 35));
         assert_eq!(68, dist.coords[5].len());
         assert!(!dist.coords[5].contains(&create_coord(5, 1, 14)));
@@ -347,7 +571,7 @@ mod circular_grid_test {
     #[test]
     fn test_remove_close_neighbours_one_to_remove_3_35_2_21() {
         let mut grid = build_circular_grid(5, 7, 0.5);
-        let mut dist = grid.circular_dist();
+        let mut dist = grid.circular_dist(MazeRng::seed_from_u64(0));
         dist.remove_close_neighbours(&create_coord(5, 3, 35));
         assert_eq!(69, dist.coords[5].len());
         assert!(!dist.coords[5].contains(&create_coord(5, 2, 21)));
@@ -355,43 +579,42 @@ mod circular_grid_test {
 
     #[test]
     fn test_take_from_outer_circle_options() {
-        let check_and_select = |c: Vec<&CircleCoordinate>| {
-            let values = vec![
-                create_coord(2, 0, 1),
-                create_coord(2, 1, 15),
-                create_coord(2, 1, 10),
-                create_coord(2, 2, 15),
-                create_coord(2, 1, 5),
-                create_coord(2, 4, 15),
-                create_coord(2, 3, 10),
-                create_coord(2, 1, 3),
-                create_coord(2, 2, 5),
-                create_coord(2, 7, 15),
-                create_coord(2, 1, 2),
-                create_coord(2, 8, 15),
-                create_coord(2, 3, 5),
-                create_coord(2, 2, 3),
-                create_coord(2, 7, 10),
-                create_coord(2, 11, 15),
-                create_coord(2, 4, 5),
-                create_coord(2, 13, 15),
-                create_coord(2, 9, 10),
-                create_coord(2, 14, 15),
-            ];
-            let expected: Vec<&CircleCoordinate> = values.iter().collect();
-
-            assert_eq!(expected, c);
-
-            c[0].to_owned()
+        let values = vec![
+            create_coord(2, 0, 1),
+            create_coord(2, 1, 15),
+            create_coord(2, 1, 10),
+            create_coord(2, 2, 15),
+            create_coord(2, 1, 5),
+            create_coord(2, 4, 15),
+            create_coord(2, 3, 10),
+            create_coord(2, 1, 3),
+            create_coord(2, 2, 5),
+            create_coord(2, 7, 15),
+            create_coord(2, 1, 2),
+            create_coord(2, 8, 15),
+            create_coord(2, 3, 5),
+            create_coord(2, 2, 3),
+            create_coord(2, 7, 10),
+            create_coord(2, 11, 15),
+            create_coord(2, 4, 5),
+            create_coord(2, 13, 15),
+            create_coord(2, 9, 10),
+            create_coord(2, 14, 15),
+        ];
+        let check_and_select = |options: usize| {
+            assert_eq!(20, options);
+            0
         };
         let mut grid = build_circular_grid(2, 5, 0.);
         let mut dist_zero_dist = grid.circular_dist_with_selector(Box::new(check_and_select));
-        dist_zero_dist.take_from_outer_circle();
+        assert_eq!(values, dist_zero_dist.coords[2]);
+        let (coord, _state) = dist_zero_dist.take_from_outer_circle();
+        assert_eq!(values[0], coord);
     }
 
     #[test]
     fn test_take_from_outer_circle_state() {
-        let select_first = |c: Vec<&CircleCoordinate>| c[0].to_owned();
+        let select_first = |_options: usize| 0;
         let mut grid = build_circular_grid(2, 5, 0.);
         let mut grid_zero_dist = grid.circular_dist_with_selector(Box::new(select_first));
 
@@ -404,7 +627,7 @@ mod circular_grid_test {
 
     #[test]
     fn test_take_from_outer_circle_neighbours_still_there() {
-        let select_second = |c: Vec<&CircleCoordinate>| c[1].to_owned();
+        let select_second = |_options: usize| 1;
         let mut grid = build_circular_grid(5, 7, 0.1);
         let mut dist = grid.circular_dist_with_selector(Box::new(select_second));
         let (coord, _state) = dist.take_from_outer_circle();
@@ -414,7 +637,7 @@ mod circular_grid_test {
 
     #[test]
     fn test_take_from_outer_circle_neighbours_gone() {
-        let select_second = |c: Vec<&CircleCoordinate>| c[1].to_owned();
+        let select_second = |_options: usize| 1;
         let mut grid = build_circular_grid(5, 7, 0.3);
         let mut dist = grid.circular_dist_with_selector(Box::new(select_second));
         let (coord, _state) = dist.take_from_outer_circle();
@@ -424,7 +647,7 @@ mod circular_grid_test {
 
     #[test]
     fn test_take_from_outer_circle_second_time_second_coordinate_is_still_there() {
-        let select_second = |c: Vec<&CircleCoordinate>| c[1].to_owned();
+        let select_second = |_options: usize| 1;
         let mut grid = build_circular_grid(2, 5, 0.);
         let mut dist = grid.circular_dist_with_selector(Box::new(select_second));
         let (coord, _state) = dist.take_from_outer_circle();
@@ -433,10 +656,22 @@ mod circular_grid_test {
         assert_eq!(create_coord(2, 1, 15), coord);
     }
 
+    #[test]
+    fn test_take_from_outer_circle_at() {
+        let mut grid = build_circular_grid(2, 5, 0.);
+        let mut dist = grid.circular_dist(MazeRng::seed_from_u64(0));
+        let (coord, _state) = dist.take_from_outer_circle_at(&Angle::new(1_u32, 4_u32));
+        assert_eq!(create_coord(2, 1, 5), coord);
+        let (coord, _state) = dist.take_from_outer_circle_at(&Angle::new(3_u32, 10_u32));
+        assert_eq!(create_coord(2, 3, 10), coord);
+        let (coord, _state) = dist.take_from_outer_circle_at(&Angle::from(0));
+        assert_eq!(create_coord(2, 0, 1), coord);
+    }
+
     #[test]
     fn test_consume_outer_circle() {
         let mut grid = build_circular_grid(2, 5, 0.);
-        let mut dist = grid.circular_dist();
+        let mut dist = grid.circular_dist(MazeRng::seed_from_u64(0));
         dist.consume_outer_circle();
         assert!(dist.coords[2].iter().all(|c| dist.taken.contains(c)));
     }
@@ -444,7 +679,7 @@ mod circular_grid_test {
     #[test]
     fn test_neighbours_on_arc() {
         let mut grid = build_circular_grid(10, 7, 0.);
-        let mut dist = grid.circular_dist();
+        let mut dist = grid.circular_dist(MazeRng::seed_from_u64(0));
 
         for pair in vec![
             pair(0, 1, 7, 0, 0, 1),
@@ -475,7 +710,7 @@ mod circular_grid_test {
     #[test]
     fn test_neighbours_on_line() {
         let mut grid = build_circular_grid(10, 7, 0.);
-        let mut dist = grid.circular_dist();
+        let mut dist = grid.circular_dist(MazeRng::seed_from_u64(0));
         let pair = pair(10, 0, 1, 9, 0, 1);
         assert_eq!(
             pair.0,
@@ -498,9 +733,77 @@ mod circular_grid_test {
         assert_eq!(56, coords.len());
     }
 
+    #[test]
+    fn test_hundreds_of_circles() {
+        let grid = build_circular_grid(600, 16, 0.);
+        let coords = grid.coords_on_circle(600);
+        assert_eq!(2 * 600 * 16, coords.len());
+        assert!(coords.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(Angle::new(1_u32, 601 * 16_u32), coords[1].angle);
+    }
+
+    #[test]
+    fn test_large_angles() {
+        let grid = build_circular_grid(5000, 1000, 0.);
+        let coord = |numer: u64, denom: u64| CircleCoordinate {
+            circle: 4000,
+            angle: Angle::new(numer, denom),
+        };
+
+        // The denominators on this circle no longer fit a u32.
+        assert_eq!(coord(1, 4_001_000), grid.next_coord_on_circle(&coord(0, 1)));
+        assert_eq!(
+            coord(4_000_999, 4_001_000),
+            grid.next_coord_on_circle(&coord(3_999_999, 4_000_000))
+        );
+        assert_eq!(
+            coord(0, 1),
+            grid.next_coord_on_circle(&coord(4_000_999, 4_001_000))
+        );
+        assert_eq!(
+            Angle::new(16_012_001_999_u64, 16_012_002_000_u64),
+            clockwise_distance(
+                &Angle::new(1_u32, 4_001_000_u32),
+                &Angle::new(1_u32, 4_002_000_u32)
+            )
+        );
+
+        assert!(fits(600, 16, &Interleaved));
+        assert_eq!(
+            cfg!(feature = "wide-angles"),
+            fits(5000, 1000, &Interleaved)
+        );
+        assert!(!fits(u32::MAX, u32::MAX, &Interleaved));
+        assert!(!fits(200, 1, &Doubling { every: 1 }));
+    }
+
+    #[test]
+    fn test_subdivisions() {
+        let rings = |subdivision: Box<dyn RingSubdivision>| {
+            let grid = circular_grid::build_circular_grid(4, 6, subdivision, 0.);
+            (0..=4)
+                .map(|circle| grid.coords_on_circle(circle).len())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![6, 12, 24, 36, 48], rings(Box::new(Interleaved)));
+        assert_eq!(vec![6, 6, 6, 6, 6], rings(Box::new(Constant)));
+        assert_eq!(
+            vec![6, 6, 12, 12, 24],
+            rings(Box::new(Doubling { every: 2 }))
+        );
+        // Rings of 6, 10, 14, 18 and 22 cells only start cells together at 0
+        // and half a turn.
+        assert_eq!(vec![6, 14, 22, 30, 38], rings(Box::new(Proportional)));
+
+        let grid = circular_grid::build_circular_grid(4, 6, Box::new(Doubling { every: 2 }), 0.);
+        let circle = grid.coords_on_circle(2);
+        assert_eq!(Angle::new(1_u32, 12_u32), circle[1].angle);
+        assert!(circle.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
     #[test]
     fn test_take() {
-        let select_first = |c: Vec<&CircleCoordinate>| c[0].to_owned();
+        let select_first = |_options: usize| 0;
         let mut grid = build_circular_grid(1, 4, 0.);
         let mut dist = grid.circular_dist_with_selector(Box::new(select_first));
 
@@ -510,4 +813,29 @@ mod circular_grid_test {
 
         assert!(dist.take_free().is_none());
     }
+
+    #[test]
+    fn test_remove_close_coords() {
+        let mut grid = build_circular_grid(5, 7, 0.5);
+        let mut dist = grid.circular_dist(MazeRng::seed_from_u64(0));
+        dist.remove_close_coords();
+
+        assert_eq!(grid.coords[0], dist.coords[0]);
+        assert!(dist.coords[4].len() < grid.coords[4].len());
+        for circle in 0..=5 {
+            for pair in dist.coords[circle].windows(2) {
+                assert!(!dist.too_close(&pair[0].angle, &pair[1].angle, circle as u32));
+            }
+        }
+    }
+
+    #[test]
+    fn test_remove_close_coords_keeps_taken() {
+        let mut grid = build_circular_grid(5, 7, 0.5);
+        let mut dist = grid.circular_dist(MazeRng::seed_from_u64(0));
+        dist.consume_outer_circle();
+        dist.remove_close_coords();
+
+        assert_eq!(grid.coords[5], dist.coords[5]);
+    }
 }
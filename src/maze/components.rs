@@ -1,6 +1,11 @@
+use std::fmt;
+
 use fraction::GenericFraction;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Direction {
     Out,
     In,
@@ -8,6 +13,24 @@ pub enum Direction {
     CounterClockwise,
 }
 
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::In,
+        Direction::Out,
+        Direction::Clockwise,
+        Direction::CounterClockwise,
+    ];
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Out => Direction::In,
+            Direction::In => Direction::Out,
+            Direction::Clockwise => Direction::CounterClockwise,
+            Direction::CounterClockwise => Direction::Clockwise,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum CellState {
     Free,
@@ -15,13 +38,34 @@ pub enum CellState {
 }
 
 pub trait Grid {
-    fn dist(&mut self) -> Box<dyn Distributor>;
+    fn dist(&mut self, rng: MazeRng) -> Box<dyn Distributor>;
+    fn circles(&self) -> Vec<Vec<CircleCoordinate>>;
 }
 
 pub trait Distributor {
     fn take_from_outer_circle(&mut self) -> (CircleCoordinate, CellState);
+    fn take_from_outer_circle_at(&mut self, angle: &Angle) -> (CircleCoordinate, CellState);
     fn consume_outer_circle(&mut self);
     fn take_free(&mut self) -> Option<CircleCoordinate>;
+    /// All coordinates that are not taken yet, ordered by circle and angle.
+    fn free(&self) -> Box<dyn Iterator<Item = &CircleCoordinate> + '_>;
+    fn free_len(&self) -> usize;
+    /// The free coordinate with `n` free coordinates before it.
+    fn nth_free(&self, n: usize) -> Option<CircleCoordinate>;
+    /// The number of free coordinates ordered before a coordinate of the grid.
+    fn free_before(&self, coord: &CircleCoordinate) -> usize;
+    fn state(&self, coord: &CircleCoordinate) -> CellState;
+    /// Applies the minimal distance to all coordinates at once, so neighbours
+    /// no longer change when coordinates are taken.
+    fn remove_close_coords(&mut self);
+    /// The coordinates that are left or taken on every circle, innermost
+    /// first. Once the walls are built, these are the corners of the cells.
+    fn coords(&self) -> Vec<Vec<CircleCoordinate>>;
+    fn neighbour(
+        &self,
+        coord: &CircleCoordinate,
+        direction: &Direction,
+    ) -> Option<CircleCoordinate>;
     fn take_neighbour(
         &mut self,
         coord: &CircleCoordinate,
@@ -29,15 +73,24 @@ pub trait Distributor {
     ) -> Option<(CircleCoordinate, CellState)>;
 }
 
-pub type Angle = GenericFraction<u32>;
+/// The integer type of the numerators and denominators of angles. Grid angles
+/// on circle `c` have denominators up to `c * (c + 1) * inner_slices`, and
+/// arithmetic between circles multiplies two of them, so the `wide-angles`
+/// feature switches to `u128` for mazes too large for `u64`.
+#[cfg(not(feature = "wide-angles"))]
+pub type AngleInt = u64;
+#[cfg(feature = "wide-angles")]
+pub type AngleInt = u128;
 
-#[derive(Debug, PartialEq)]
+pub type Angle = GenericFraction<AngleInt>;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum BorderType {
     Arc,
     Line,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Border {
     pub start: CircleCoordinate,
     pub end: CircleCoordinate,
@@ -53,23 +106,177 @@ impl Border {
     }
 }
 
-pub fn random_nr(upper_bound: usize) -> usize {
-    (rand::random::<f32>() * upper_bound as f32).floor() as usize
+/// Where an opening in the outer circle is placed.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Placement {
+    /// Anywhere. A random exit that lands on the entrance is moved half a turn.
+    Random,
+    /// At a fraction of a full turn. An exit here that lands on the entrance is
+    /// an invalid parameter.
+    At(f64),
+}
+
+/// Shapes the corridors by making some steps of the walls more likely than
+/// others. Every step is chosen with a chance proportional to the weight of its
+/// direction, multiplied by `straight` when it continues in the direction of the
+/// step before. The default picks every step with the same chance.
+///
+/// Used by random walks, the recursive backtracker, growing tree and
+/// hunt-and-kill; the other algorithms need unbiased choices.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bias {
+    pub inward: f64,
+    pub outward: f64,
+    pub clockwise: f64,
+    pub counter_clockwise: f64,
+    pub straight: f64,
+}
+
+impl Default for Bias {
+    fn default() -> Self {
+        Bias {
+            inward: 1.,
+            outward: 1.,
+            clockwise: 1.,
+            counter_clockwise: 1.,
+            straight: 1.,
+        }
+    }
+}
+
+impl Bias {
+    pub fn weight(&self, direction: Direction, straight: bool) -> f64 {
+        let weight = match direction {
+            Direction::In => self.inward,
+            Direction::Out => self.outward,
+            Direction::Clockwise => self.clockwise,
+            Direction::CounterClockwise => self.counter_clockwise,
+        };
+        if straight {
+            weight * self.straight
+        } else {
+            weight
+        }
+    }
+}
+
+/// A gap in the outer circle, running clockwise from `start` to `end`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Opening {
+    pub start: CircleCoordinate,
+    pub end: CircleCoordinate,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Openings {
+    pub entrance: Opening,
+    pub exit: Option<Opening>,
+}
+
+/// Where the route through the maze ends.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Goal {
+    Centre,
+    Exit,
+    /// The cell on the ring at a fraction of a full turn.
+    Ring {
+        ring: u32,
+        angle: f64,
+    },
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+/// Converts a fraction of a full turn to an angle in `[0, 1)`, rounded to a
+/// millionth of a turn.
+pub fn angle_from_turn(turn: f64) -> Angle {
+    const PRECISION: AngleInt = 1_000_000;
+    let numer = (turn.rem_euclid(1.) * PRECISION as f64).round() as AngleInt % PRECISION;
+    Angle::new(numer, PRECISION)
+}
+
+pub type MazeRng = ChaCha8Rng;
+
+pub fn random_nr(rng: &mut MazeRng, upper_bound: usize) -> usize {
+    rng.gen_range(0..upper_bound)
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct CircleCoordinate {
     pub circle: u32,
+    #[serde(with = "exact_angle")]
     pub angle: Angle,
 }
 
+/// Shows the circle and the angle as a fraction of a full turn, such as `2:3/8`.
+impl fmt::Display for CircleCoordinate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.circle, self.angle)
+    }
+}
+
+impl fmt::Display for Border {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the wall from {} to {}", self.start, self.end)
+    }
+}
+
+/// Stores angles as exact fractions of a full turn, such as `"3/8"`.
+mod exact_angle {
+    use serde::{de, ser, Deserialize, Deserializer, Serializer};
+
+    use super::{Angle, AngleInt};
+
+    pub fn serialize<S: Serializer>(angle: &Angle, serializer: S) -> Result<S::Ok, S::Error> {
+        match (angle.numer(), angle.denom()) {
+            (Some(numer), Some(denom)) => serializer.serialize_str(&format!("{}/{}", numer, denom)),
+            _ => Err(ser::Error::custom(format!("{} is not a fraction", angle))),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Angle, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        parse(&value).ok_or_else(|| {
+            de::Error::custom(format!("{} is not a fraction of a turn in [0, 1)", value))
+        })
+    }
+
+    fn parse(value: &str) -> Option<Angle> {
+        let (numer, denom) = value.split_once('/').unwrap_or((value, "1"));
+        let numer: AngleInt = numer.trim().parse().ok()?;
+        let denom: AngleInt = denom.trim().parse().ok()?;
+        (denom > 0 && numer < denom).then(|| Angle::new(numer, denom))
+    }
+}
+
 #[cfg(test)]
 mod components_test {
     use crate::maze::{
-        components::BorderType,
+        components::{BorderType, CircleCoordinate},
         test_utils::helper_fns::{create_border, create_coord},
     };
 
+    #[test]
+    fn test_exact_angle() {
+        let coord = create_coord(2, 3, 8);
+        let json = serde_json::to_string(&coord).unwrap();
+        assert_eq!(r#"{"circle":2,"angle":"3/8"}"#, json);
+        assert_eq!(coord, serde_json::from_str(&json).unwrap());
+        assert_eq!(
+            create_coord(0, 0, 1),
+            serde_json::from_str(r#"{"circle":0,"angle":"0"}"#).unwrap()
+        );
+
+        for angle in ["1/0", "5/4", "1", "-1/2", "a/b", "0.5"] {
+            let json = format!(r#"{{"circle":0,"angle":"{}"}}"#, angle);
+            assert!(
+                serde_json::from_str::<CircleCoordinate>(&json).is_err(),
+                "{}",
+                angle
+            );
+        }
+    }
+
     #[test]
     fn test_border_type() {
         assert_eq!(
@@ -0,0 +1,884 @@
+use std::{fs, path::Path};
+
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dxf::{self, draw::DxfOptions},
+    pdf::{self, draw::PdfOptions},
+    plot::{
+        self,
+        draw::{PlotFormat, PlotOptions},
+    },
+    png::{self, draw::PngOptions},
+    svg::{
+        draw,
+        order::DrawingOrder,
+        parse::{Canvas, Parser},
+    },
+    Error, Result,
+};
+
+use super::{
+    algorithms::{AldousBroder, Algorithm},
+    braid, circular_grid,
+    components::{
+        angle_from_turn, Bias, Border, BorderType, CircleCoordinate, Goal, Grid, MazeRng, Openings,
+        Placement,
+    },
+    graph::MazeGraph,
+    maze_builder::build_maze,
+    metrics::{Difficulty, Metrics},
+    radii::{Radii, Spacing},
+    solver::{self, RoutePoint},
+    subdivision::Subdivision,
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MazeConfig {
+    circles: u32,
+    inner_slices: u32,
+    subdivision: Subdivision,
+    spacing: Spacing,
+    inner_radius: f64,
+    min_distance: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    entrance: Placement,
+    exit: Option<Placement>,
+    goal: Goal,
+    algorithm: Algorithm,
+    bias: Bias,
+    braid: f64,
+}
+
+impl Default for MazeConfig {
+    fn default() -> Self {
+        MazeConfig {
+            circles: 5,
+            inner_slices: 10,
+            subdivision: Subdivision::Interleaved,
+            spacing: Spacing::Linear,
+            inner_radius: 1.,
+            min_distance: 0.3,
+            seed: None,
+            entrance: Placement::Random,
+            exit: None,
+            goal: Goal::Centre,
+            algorithm: Algorithm::RandomWalk,
+            bias: Bias::default(),
+            braid: 0.,
+        }
+    }
+}
+
+impl MazeConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of circles, including the outer wall.
+    pub fn circles(mut self, circles: u32) -> Self {
+        self.circles = circles;
+        self
+    }
+
+    /// Number of slices on the innermost circle.
+    pub fn inner_slices(mut self, inner_slices: u32) -> Self {
+        self.inner_slices = inner_slices;
+        self
+    }
+
+    /// How many cells each ring is divided into; every ring has `inner_slices`
+    /// more cells than the one inside it by default.
+    pub fn subdivision(mut self, subdivision: Subdivision) -> Self {
+        self.subdivision = subdivision;
+        self
+    }
+
+    /// How far apart the circles are; every ring is equally wide by default.
+    pub fn spacing(mut self, spacing: Spacing) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Radius of the innermost circle, in widths of an equally wide ring. A
+    /// radius above 1 leaves a larger room in the centre. Custom spacing sets
+    /// its own inner radius.
+    pub fn inner_radius(mut self, inner_radius: f64) -> Self {
+        self.inner_radius = inner_radius;
+        self
+    }
+
+    /// Minimal distance between two points on the same circle.
+    pub fn min_distance(mut self, min_distance: f64) -> Self {
+        self.min_distance = min_distance;
+        self
+    }
+
+    /// Seed for the random number generator; a random seed is used when none is set.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Where the entrance in the outer circle is placed; random by default.
+    pub fn entrance(mut self, entrance: Placement) -> Self {
+        self.entrance = entrance;
+        self
+    }
+
+    /// Adds a second opening to the outer circle.
+    pub fn exit(mut self, exit: Placement) -> Self {
+        self.exit = Some(exit);
+        self
+    }
+
+    /// Where the route through the maze ends; the centre by default.
+    pub fn goal(mut self, goal: Goal) -> Self {
+        self.goal = goal;
+        self
+    }
+
+    /// The algorithm that grows the walls; random walks by default.
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Makes some directions of the walls more likely than others; unbiased by
+    /// default.
+    pub fn bias(mut self, bias: Bias) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Percentage of dead ends, from 0 to 100, that are removed by opening an
+    /// extra wall, adding loops to the maze. None by default.
+    pub fn braid(mut self, braid: f64) -> Self {
+        self.braid = braid;
+        self
+    }
+
+    /// Checks that every parameter is in range, so that a maze can be built.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: String| Err(Error::InvalidParameter(message));
+        let is_turn = |turn: f64| (0. ..1.).contains(&turn);
+
+        if self.circles < 2 {
+            return invalid(format!(
+                "a maze needs at least 2 circles, not {}",
+                self.circles
+            ));
+        }
+        if self.inner_slices == 0 {
+            return invalid("the innermost circle needs at least 1 slice".to_string());
+        }
+        if let Subdivision::Doubling { every: 0 } = self.subdivision {
+            return invalid("the cells cannot double every 0 rings".to_string());
+        }
+        let subdivision = self.subdivision.ring_subdivision();
+        if !circular_grid::fits(self.circles - 1, self.inner_slices, &*subdivision) {
+            return invalid(format!(
+                "{} circles with {} inner slices are too many for the angles of the grid",
+                self.circles, self.inner_slices
+            ));
+        }
+        // Points on the innermost circle are 1 apart.
+        if !(0. ..1.).contains(&self.min_distance) {
+            return invalid(format!(
+                "the minimal distance {} is not in [0, 1)",
+                self.min_distance
+            ));
+        }
+        // Points closer than the minimal distance are removed, which must not
+        // take away the start of a cell. The outermost ring lies beyond the
+        // outer circle and holds the openings.
+        let rings = (0..self.circles)
+            .filter_map(|ring| Some((ring, subdivision.cells(ring, self.inner_slices)?)));
+        for (ring, cells) in rings {
+            if ring > 0 && cells < 2 {
+                return invalid(format!(
+                    "ring {} needs at least 2 cells, not {}",
+                    ring, cells
+                ));
+            }
+            let width = f64::from(ring + 1) * f64::from(self.inner_slices) / f64::from(cells);
+            if self.min_distance >= width {
+                return invalid(format!(
+                    "the cells of ring {} are narrower than the minimal distance {}",
+                    ring, self.min_distance
+                ));
+            }
+        }
+        if self.algorithm == Algorithm::AldousBroder {
+            let cells: u64 = (0..self.circles - 1)
+                .filter_map(|ring| subdivision.cells(ring, self.inner_slices))
+                .map(u64::from)
+                .sum();
+            if cells > AldousBroder::MAX_CELLS {
+                return invalid(format!(
+                    "aldous-broder takes too long for {} cells, it allows at most {}",
+                    cells,
+                    AldousBroder::MAX_CELLS
+                ));
+            }
+        }
+        if !(self.inner_radius.is_finite() && self.inner_radius > 0.) {
+            return invalid(format!(
+                "the inner radius {} is not above 0",
+                self.inner_radius
+            ));
+        }
+        if let Spacing::Custom(radii) = &self.spacing {
+            if radii.len() != self.circles as usize {
+                return invalid(format!(
+                    "{} radii are given for {} circles",
+                    radii.len(),
+                    self.circles
+                ));
+            }
+            let grows = |pair: &[f64]| pair[0] < pair[1] && pair[1].is_finite();
+            if !(radii[0] > 0. && radii.windows(2).all(grows)) {
+                return invalid(format!("the radii {:?} do not grow from above 0", radii));
+            }
+        }
+        for placement in [Some(self.entrance), self.exit].into_iter().flatten() {
+            if let Placement::At(turn) = placement {
+                if !is_turn(turn) {
+                    return invalid(format!("{} is not a fraction of a turn in [0, 1)", turn));
+                }
+            }
+        }
+        if let (Placement::At(entrance), Some(Placement::At(exit))) = (self.entrance, self.exit) {
+            let mut dist = self.grid().dist(MazeRng::seed_from_u64(0));
+            let (entrance_coord, _) = dist.take_from_outer_circle_at(&angle_from_turn(entrance));
+            let (exit_coord, _) = dist.take_from_outer_circle_at(&angle_from_turn(exit));
+            if entrance_coord == exit_coord {
+                return invalid(format!(
+                    "the exit at {} lies on the entrance at {}",
+                    exit, entrance
+                ));
+            }
+        }
+        match self.goal {
+            Goal::Exit if self.exit.is_none() => {
+                return invalid("the goal is the exit, but the maze has none".to_string())
+            }
+            Goal::Ring { ring, angle } if ring + 1 >= self.circles || !is_turn(angle) => {
+                return invalid(format!("there is no ring {} at {}", ring, angle));
+            }
+            _ => {}
+        }
+
+        let bias = &self.bias;
+        let weights = [
+            bias.inward,
+            bias.outward,
+            bias.clockwise,
+            bias.counter_clockwise,
+        ];
+        let valid = |weight: &f64| weight.is_finite() && *weight >= 0.;
+        if !weights.iter().all(valid) || !valid(&bias.straight) {
+            return invalid(format!("the weights of {:?} are not all 0 or more", bias));
+        }
+        if weights.iter().all(|weight| *weight == 0.) {
+            return invalid("at least one direction needs a weight above 0".to_string());
+        }
+        if !(0. ..=100.).contains(&self.braid) {
+            return invalid(format!("{} is not a percentage from 0 to 100", self.braid));
+        }
+        Ok(())
+    }
+
+    fn grid(&self) -> Box<dyn Grid> {
+        circular_grid::build_subdivided(
+            self.circles - 1,
+            self.inner_slices,
+            self.subdivision.ring_subdivision(),
+            self.min_distance,
+        )
+    }
+
+    pub fn build(&self) -> Result<Maze> {
+        self.validate()?;
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let mut rng = MazeRng::seed_from_u64(seed);
+
+        let dist = self.grid().dist(MazeRng::seed_from_u64(rng.gen()));
+        let (mut borders, openings, grid_circles) = build_maze(
+            dist,
+            rng,
+            &*self.algorithm.maze_algorithm(),
+            self.bias,
+            self.entrance,
+            self.exit,
+        )?;
+        if self.braid > 0. {
+            // A stream of its own keeps the walls of unbraided mazes the same.
+            let mut rng = MazeRng::seed_from_u64(seed);
+            rng.set_stream(1);
+            let mut graph = MazeGraph::from_borders(&grid_circles, &borders, &openings);
+            braid::braid(&mut graph, self.braid, &mut rng);
+            borders = graph.to_borders();
+        }
+
+        Ok(Maze {
+            seed,
+            config: MazeConfig {
+                seed: None,
+                ..self.clone()
+            },
+            grid_circles,
+            borders,
+            openings,
+        })
+    }
+
+    /// Builds mazes with consecutive seeds, starting at the configured seed,
+    /// until one falls inside the difficulty band. The seed of the maze that is
+    /// returned reproduces it. Returns `None` when no maze fits within
+    /// `attempts` tries.
+    pub fn build_within(&self, difficulty: &Difficulty, attempts: u32) -> Result<Option<Maze>> {
+        let first_seed = self.seed.unwrap_or_else(rand::random);
+        for attempt in 0..attempts as u64 {
+            let maze = self
+                .clone()
+                .seed(first_seed.wrapping_add(attempt))
+                .build()?;
+            if difficulty.is_unbounded() || difficulty.contains(&maze.metrics()) {
+                return Ok(Some(maze));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// A generated maze. It is saved as JSON together with the configuration and
+/// the grid it was built on, with angles as exact fractions of a full turn.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Maze {
+    seed: u64,
+    config: MazeConfig,
+    #[serde(rename = "grid")]
+    grid_circles: Vec<Vec<CircleCoordinate>>,
+    borders: Vec<Border>,
+    openings: Openings,
+}
+
+impl Maze {
+    /// Reads a maze saved with [`Maze::write_json`].
+    pub fn read_json(input: &Path) -> Result<Maze> {
+        Maze::from_json(&fs::read_to_string(input)?)
+    }
+
+    /// Reads a saved maze, checking that its walls and openings lie on its grid.
+    pub fn from_json(json: &str) -> Result<Maze> {
+        let maze: Maze = serde_json::from_str(json)?;
+        maze.config.validate()?;
+        maze.validate().map_err(Error::InvalidMaze)?;
+        Ok(maze)
+    }
+
+    fn validate(&self) -> std::result::Result<(), String> {
+        let grid = &self.grid_circles;
+        if grid.len() != self.circles() as usize || grid.len() < 2 {
+            return Err(format!(
+                "the grid has {} circles instead of {}",
+                grid.len(),
+                self.circles()
+            ));
+        }
+        for (circle, coords) in grid.iter().enumerate() {
+            if coords.is_empty() || coords.iter().any(|c| c.circle as usize != circle) {
+                return Err(format!("circle {} of the grid is not valid", circle));
+            }
+            if coords.windows(2).any(|pair| pair[0].angle >= pair[1].angle) {
+                return Err(format!(
+                    "the angles of circle {} are not increasing",
+                    circle
+                ));
+            }
+        }
+
+        let on_grid = |coord: &CircleCoordinate| {
+            grid.get(coord.circle as usize)
+                .is_some_and(|coords| coords.binary_search(coord).is_ok())
+        };
+        for border in &self.borders {
+            if !on_grid(&border.start) || !on_grid(&border.end) {
+                return Err(format!("{} is not on the grid", border));
+            }
+            if border.border_type() == BorderType::Line {
+                // A line runs outward and crosses the circles in between on grid points.
+                let crossed = (border.start.circle..border.end.circle).all(|circle| {
+                    on_grid(&CircleCoordinate {
+                        circle,
+                        angle: border.start.angle,
+                    })
+                });
+                if border.start.circle > border.end.circle
+                    || border.start.angle != border.end.angle
+                    || !crossed
+                {
+                    return Err(format!("{} is neither an arc nor a line", border));
+                }
+            }
+        }
+
+        let outer = grid.len() as u32 - 1;
+        for opening in [Some(&self.openings.entrance), self.openings.exit.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            for coord in [&opening.start, &opening.end] {
+                if coord.circle != outer || !on_grid(coord) {
+                    return Err(format!(
+                        "the opening from {} to {} is not on the outer circle",
+                        opening.start, opening.end
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn circles(&self) -> u32 {
+        self.config.circles
+    }
+
+    /// The seed that reproduces this maze.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The configuration the maze was built with. Together with the seed it
+    /// builds the same maze again.
+    pub fn config(&self) -> &MazeConfig {
+        &self.config
+    }
+
+    pub fn borders(&self) -> &[Border] {
+        &self.borders
+    }
+
+    /// The openings in the outer circle.
+    pub fn openings(&self) -> &Openings {
+        &self.openings
+    }
+
+    pub fn goal(&self) -> Goal {
+        self.config.goal
+    }
+
+    /// The distances of the circles from the centre.
+    pub fn radii(&self) -> Radii {
+        self.config
+            .spacing
+            .radii(self.config.circles, self.config.inner_radius)
+    }
+
+    /// The cells of the maze and the passages between them.
+    pub fn graph(&self) -> MazeGraph {
+        MazeGraph::from_borders(&self.grid_circles, &self.borders, &self.openings)
+    }
+
+    /// The shortest route from the entrance to the goal.
+    pub fn solution(&self) -> Option<Vec<RoutePoint>> {
+        solver::solve_graph(&self.graph(), &self.config.goal)
+    }
+
+    /// Numbers that describe how hard the maze is to solve.
+    pub fn metrics(&self) -> Metrics {
+        Metrics::new(&self.graph(), &self.config.goal)
+    }
+
+    /// An order of drawing the borders that keeps the travel with the pen up short.
+    pub fn drawing_order(&self) -> DrawingOrder {
+        DrawingOrder::optimise(&self.borders, &self.radii())
+    }
+
+    /// Draws the walls on a canvas on which a ring of an evenly spaced maze is
+    /// `ring_width` wide.
+    pub fn render<T: Canvas>(&self, ring_width: u32, canvas: T) -> T {
+        Parser::new(&self.radii(), ring_width, &self.borders).parse(canvas)
+    }
+
+    pub fn write_svg(&self, template: &Path, output: &Path) -> Result<()> {
+        draw::draw(&self.radii(), &self.borders, None, template, output)
+    }
+
+    /// Writes the maze with its solution drawn on top of it.
+    pub fn write_svg_with_solution(&self, template: &Path, output: &Path) -> Result<()> {
+        let solution = self.solution().unwrap_or_default();
+        draw::draw(
+            &self.radii(),
+            &self.borders,
+            Some(&solution),
+            template,
+            output,
+        )
+    }
+
+    pub fn write_png(&self, options: &PngOptions, output: &Path) -> Result<()> {
+        png::draw::draw(&self.radii(), &self.borders, None, options, output)
+    }
+
+    /// Writes the maze with its solution drawn on top of it.
+    pub fn write_png_with_solution(&self, options: &PngOptions, output: &Path) -> Result<()> {
+        let solution = self.solution().unwrap_or_default();
+        png::draw::draw(
+            &self.radii(),
+            &self.borders,
+            Some(&solution),
+            options,
+            output,
+        )
+    }
+
+    pub fn write_pdf(&self, options: &PdfOptions, output: &Path) -> Result<()> {
+        pdf::draw::draw(&[self], false, options, output)
+    }
+
+    /// Writes the maze with its solution drawn on top of it.
+    pub fn write_pdf_with_solution(&self, options: &PdfOptions, output: &Path) -> Result<()> {
+        pdf::draw::draw(&[self], true, options, output)
+    }
+
+    /// Writes the maze as G-code for pen plotters, laser cutters and CNC machines.
+    pub fn write_gcode(&self, options: &PlotOptions, output: &Path) -> Result<()> {
+        plot::draw::draw(
+            &self.radii(),
+            &self.borders,
+            PlotFormat::Gcode,
+            options,
+            output,
+        )
+    }
+
+    /// Writes the maze as HPGL for pen plotters and cutters.
+    pub fn write_hpgl(&self, options: &PlotOptions, output: &Path) -> Result<()> {
+        plot::draw::draw(
+            &self.radii(),
+            &self.borders,
+            PlotFormat::Hpgl,
+            options,
+            output,
+        )
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Saves the maze, so that it can be drawn or solved again without
+    /// generating it.
+    pub fn write_json(&self, output: &Path) -> Result<()> {
+        fs::write(output, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Writes the maze as DXF with true arcs, for CAD and laser cutter software.
+    pub fn write_dxf(&self, options: &DxfOptions, output: &Path) -> Result<()> {
+        dxf::draw::draw(&self.radii(), &self.borders, options, output)
+    }
+}
+
+#[cfg(test)]
+mod config_test {
+    use crate::{
+        maze::{
+            components::{Bias, Goal, Placement},
+            metrics::Difficulty,
+            radii::{Radii, Spacing},
+            subdivision::Subdivision,
+        },
+        Algorithm, Error,
+    };
+
+    use super::{Maze, MazeConfig};
+
+    #[test]
+    fn test_seed_is_reported() {
+        let maze = MazeConfig::new().seed(12).build().unwrap();
+        assert_eq!(12, maze.seed());
+
+        let maze = MazeConfig::new().build().unwrap();
+        let rebuilt = MazeConfig::new().seed(maze.seed()).build().unwrap();
+        assert_eq!(maze.borders(), rebuilt.borders());
+    }
+
+    #[test]
+    fn test_goals() {
+        let config = MazeConfig::new().seed(3).exit(Placement::Random);
+        for goal in [
+            Goal::Centre,
+            Goal::Exit,
+            Goal::Ring {
+                ring: 2,
+                angle: 0.6,
+            },
+        ] {
+            let maze = config.clone().goal(goal).build().unwrap();
+            assert!(maze.openings().exit.is_some());
+            assert!(maze.solution().is_some(), "no solution for {:?}", goal);
+        }
+    }
+
+    #[test]
+    fn test_subdivisions() {
+        let config = MazeConfig::new().seed(5).circles(7).inner_slices(6);
+        let interleaved = config.clone().build().unwrap();
+        for subdivision in [
+            Subdivision::Interleaved,
+            Subdivision::Constant,
+            Subdivision::Doubling { every: 2 },
+            Subdivision::Proportional,
+        ] {
+            let maze = config.clone().subdivision(subdivision).build().unwrap();
+            assert!(
+                maze.solution().is_some(),
+                "no solution for {:?}",
+                subdivision
+            );
+            if subdivision == Subdivision::Interleaved {
+                assert_eq!(interleaved.borders(), maze.borders());
+            }
+        }
+
+        let constant = config.subdivision(Subdivision::Constant).build().unwrap();
+        assert_eq!(6, constant.graph().circles()[6].len());
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let config = MazeConfig::new().seed(1);
+        for invalid in [
+            config.clone().circles(0),
+            config.clone().circles(1),
+            config.clone().inner_slices(0),
+            config.clone().min_distance(-0.1),
+            config.clone().min_distance(1.),
+            config.clone().min_distance(f64::NAN),
+            config.clone().circles(u32::MAX).inner_slices(u32::MAX),
+            config
+                .clone()
+                .subdivision(Subdivision::Doubling { every: 0 }),
+            config
+                .clone()
+                .circles(40)
+                .subdivision(Subdivision::Doubling { every: 1 }),
+            config
+                .clone()
+                .inner_slices(1)
+                .subdivision(Subdivision::Constant),
+            config
+                .clone()
+                .min_distance(0.5)
+                .subdivision(Subdivision::Doubling { every: 1 }),
+            config
+                .clone()
+                .circles(100)
+                .algorithm(Algorithm::AldousBroder),
+            config.clone().inner_radius(0.),
+            config.clone().inner_radius(f64::NAN),
+            config.clone().spacing(Spacing::Custom(vec![1., 2.])),
+            config
+                .clone()
+                .circles(3)
+                .spacing(Spacing::Custom(vec![1., 3., 2.])),
+            config.clone().braid(101.),
+            config.clone().entrance(Placement::At(1.5)),
+            config
+                .clone()
+                .entrance(Placement::At(0.5))
+                .exit(Placement::At(0.5)),
+            config
+                .clone()
+                .inner_slices(4)
+                .entrance(Placement::At(0.1))
+                .exit(Placement::At(0.12)),
+            config.clone().goal(Goal::Exit),
+            config.clone().goal(Goal::Ring { ring: 9, angle: 0. }),
+            config.clone().bias(Bias {
+                inward: 0.,
+                outward: 0.,
+                clockwise: 0.,
+                counter_clockwise: 0.,
+                straight: 1.,
+            }),
+            config.clone().bias(Bias {
+                straight: -1.,
+                ..Bias::default()
+            }),
+        ] {
+            assert!(
+                matches!(invalid.build(), Err(Error::InvalidParameter(_))),
+                "{:?}",
+                invalid
+            );
+        }
+        assert!(config.clone().min_distance(0.99).build().is_ok());
+        assert!(config
+            .clone()
+            .circles(40)
+            .inner_slices(4)
+            .algorithm(Algorithm::AldousBroder)
+            .build()
+            .is_ok());
+        assert!(config.circles(2).inner_slices(1).build().is_ok());
+    }
+
+    #[test]
+    fn test_build_within() {
+        let config = MazeConfig::new().seed(20).circles(6);
+        let difficulty = Difficulty {
+            solution_percent: Some((10., 20.)),
+            min_dead_ends: Some(15),
+            max_dead_ends: None,
+        };
+
+        let maze = config.build_within(&difficulty, 100).unwrap().unwrap();
+        assert!(maze.seed() >= 20);
+        assert!(difficulty.contains(&maze.metrics()));
+        let rebuilt = config.clone().seed(maze.seed()).build().unwrap();
+        assert_eq!(maze.borders(), rebuilt.borders());
+
+        let impossible = Difficulty {
+            min_dead_ends: Some(10_000),
+            ..Difficulty::default()
+        };
+        assert!(config.build_within(&impossible, 3).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_bias() {
+        let config = MazeConfig::new().seed(3).circles(8);
+        let circular = config
+            .clone()
+            .bias(Bias {
+                inward: 0.1,
+                outward: 0.1,
+                clockwise: 5.,
+                counter_clockwise: 5.,
+                straight: 8.,
+            })
+            .build()
+            .unwrap();
+        let radial = config
+            .clone()
+            .bias(Bias {
+                inward: 5.,
+                outward: 5.,
+                clockwise: 0.1,
+                counter_clockwise: 0.1,
+                straight: 1.,
+            })
+            .build()
+            .unwrap();
+
+        assert!(circular.solution().is_some());
+        assert!(radial.solution().is_some());
+        let (circular, radial) = (circular.metrics(), radial.metrics());
+        assert!(circular.river_factor > radial.river_factor);
+        assert!(circular.longest_corridor > radial.longest_corridor);
+
+        let unbiased = config.clone().bias(Bias::default()).build().unwrap();
+        assert_eq!(config.build().unwrap().borders(), unbiased.borders());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let maze = MazeConfig::new()
+            .seed(9)
+            .circles(4)
+            .exit(Placement::At(0.5))
+            .goal(Goal::Exit)
+            .algorithm(Algorithm::Prim)
+            .build()
+            .unwrap();
+        let json = maze.to_json().unwrap();
+        let loaded = Maze::from_json(&json).unwrap();
+
+        assert_eq!(maze, loaded);
+        assert_eq!(maze.solution(), loaded.solution());
+        assert!(json.contains(r#""algorithm": "prim""#));
+        assert!(json.contains(r#""seed": 9"#));
+
+        let rebuilt = loaded.config().clone().seed(loaded.seed()).build().unwrap();
+        assert_eq!(maze, rebuilt);
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let maze = MazeConfig::new()
+            .seed(4)
+            .circles(3)
+            .inner_slices(4)
+            .build()
+            .unwrap();
+        let json = maze.to_json().unwrap();
+        let edited = |edit: &dyn Fn(&mut serde_json::Value)| {
+            let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+            edit(&mut value);
+            Maze::from_json(&value.to_string())
+        };
+
+        assert!(edited(&|_| {}).is_ok());
+        assert!(Maze::from_json("{}").is_err());
+
+        // An angle that is not on the circle.
+        let error = edited(&|maze| maze["borders"][0]["end"]["angle"] = "1/7".into());
+        assert!(error.unwrap_err().to_string().contains("not on the grid"));
+        // A circle beyond the outer one.
+        let error = edited(&|maze| maze["borders"][0]["start"]["circle"] = 9.into());
+        assert!(error.unwrap_err().to_string().contains("not on the grid"));
+        // A line running outward from the wrong end.
+        let error = edited(&|maze| {
+            maze["borders"] = serde_json::json!([{
+                "start": {"circle": 1, "angle": "0/1"},
+                "end": {"circle": 0, "angle": "0/1"}
+            }])
+        });
+        assert!(error
+            .unwrap_err()
+            .to_string()
+            .contains("neither an arc nor a line"));
+        // A grid that does not match the number of circles.
+        let error = edited(&|maze| maze["config"]["circles"] = 4.into());
+        assert!(error.unwrap_err().to_string().contains("grid"));
+        let error = edited(&|maze| maze["openings"]["entrance"]["start"]["circle"] = 0.into());
+        assert!(error.unwrap_err().to_string().contains("outer circle"));
+    }
+
+    #[test]
+    fn test_radii() {
+        let config = MazeConfig::new().seed(2).circles(4);
+        let maze = config.clone().inner_radius(3.).build().unwrap();
+        assert_eq!(Radii::new(vec![3., 4., 5., 6.]), maze.radii());
+        assert_eq!(config.clone().build().unwrap().borders(), maze.borders());
+
+        let spacing = Spacing::Custom(vec![2., 2.5, 4., 7.]);
+        let maze = config.spacing(spacing.clone()).build().unwrap();
+        let loaded = Maze::from_json(&maze.to_json().unwrap()).unwrap();
+        assert_eq!(spacing.radii(4, 1.), loaded.radii());
+    }
+
+    #[test]
+    fn test_partial_config() {
+        let config: MazeConfig = serde_json::from_str(r#"{"circles": 7, "seed": 3}"#).unwrap();
+        assert_eq!(MazeConfig::new().circles(7).seed(3), config);
+    }
+
+    #[test]
+    fn test_circles() {
+        let maze = MazeConfig::new()
+            .circles(3)
+            .inner_slices(4)
+            .build()
+            .unwrap();
+        assert_eq!(3, maze.circles());
+        assert!(maze.borders().iter().all(|b| b.end.circle < 3));
+    }
+}
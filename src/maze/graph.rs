@@ -0,0 +1,597 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::components::{
+    Angle, Border, BorderType, CircleCoordinate, Direction, Opening, Openings,
+};
+
+pub type CellId = usize;
+
+/// A cell of the maze: the centre, the part of a ring between two lines, or
+/// the outside of the maze beyond the entrance or the exit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cell {
+    /// The space between circle `ring` and circle `ring + 1`; `None` for the
+    /// centre. Cells outside the maze are on the ring of the outer circle.
+    pub ring: Option<u32>,
+    pub start: Angle,
+    pub width: Angle,
+}
+
+/// The way from a cell to one of its neighbours.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Passage {
+    pub cell: CellId,
+    pub direction: Direction,
+    /// Where the passage is: the angle of the line between two cells on the
+    /// same ring, or the middle of the arc between two rings.
+    pub angle: Angle,
+    pub open: bool,
+}
+
+/// The cells of a maze and the passages between them, open where there is
+/// no wall. Cells are numbered from the centre outward and clockwise within
+/// a ring; the entrance and the exit come last.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MazeGraph {
+    circles: Vec<Vec<CircleCoordinate>>,
+    openings: Openings,
+    cells: Vec<Cell>,
+    ring_starts: Vec<CellId>,
+    ring_positions: Vec<Vec<Angle>>,
+    passages: Vec<Vec<Passage>>,
+    entrance: CellId,
+    exit: Option<CellId>,
+}
+
+impl MazeGraph {
+    pub const CENTRE: CellId = 0;
+
+    /// Builds the graph from the borders. `circles` are the coordinates of the
+    /// grid the borders were built on, innermost circle first.
+    pub fn from_borders(
+        circles: &[Vec<CircleCoordinate>],
+        borders: &[Border],
+        openings: &Openings,
+    ) -> Self {
+        let outer_circle = circles.len() - 1;
+        let walls = Walls::new(borders);
+
+        let mut cells = vec![Cell {
+            ring: None,
+            start: Angle::from(0),
+            width: Angle::from(1),
+        }];
+        let mut ring_starts = Vec::new();
+        let mut ring_positions = Vec::new();
+        for ring in 0..outer_circle {
+            let positions: Vec<Angle> = circles[ring]
+                .iter()
+                .map(|c| c.angle)
+                .filter(|a| {
+                    circles[ring + 1]
+                        .binary_search_by(|c| c.angle.cmp(a))
+                        .is_ok()
+                })
+                .collect();
+
+            ring_starts.push(cells.len());
+            if positions.len() < 2 {
+                cells.push(Cell {
+                    ring: Some(ring as u32),
+                    start: positions.first().copied().unwrap_or_else(|| Angle::from(0)),
+                    width: Angle::from(1),
+                });
+            } else {
+                for (index, start) in positions.iter().enumerate() {
+                    let end = positions[(index + 1) % positions.len()];
+                    cells.push(Cell {
+                        ring: Some(ring as u32),
+                        start: *start,
+                        width: clockwise_distance(start, &end),
+                    });
+                }
+            }
+            ring_positions.push(positions);
+        }
+
+        let outside = Cell {
+            ring: Some(outer_circle as u32),
+            start: Angle::from(0),
+            width: Angle::from(1),
+        };
+        let entrance = cells.len();
+        cells.push(outside.clone());
+        let exit = openings.exit.as_ref().map(|_| {
+            cells.push(outside);
+            entrance + 1
+        });
+
+        let mut graph = MazeGraph {
+            circles: circles.to_vec(),
+            openings: openings.clone(),
+            passages: vec![Vec::new(); cells.len()],
+            cells,
+            ring_starts,
+            ring_positions,
+            entrance,
+            exit,
+        };
+
+        for ring in 0..outer_circle {
+            let positions = graph.ring_positions[ring].clone();
+            let start = graph.ring_starts[ring];
+            for (index, angle) in positions.iter().enumerate() {
+                let before = start + (index + positions.len() - 1) % positions.len();
+                let open = !walls.has_line(ring as u32, angle);
+                graph.link(before, start + index, Direction::Clockwise, *angle, open);
+            }
+        }
+
+        for (circle, coords) in circles.iter().enumerate() {
+            for (index, coord) in coords.iter().enumerate() {
+                let width = arc_width(&coord.angle, &coords[(index + 1) % coords.len()].angle);
+                if let Some((inner, outer, middle)) = graph.across(circle, &coord.angle, &width) {
+                    let open = !walls.has_arc(circle as u32, &coord.angle, &width);
+                    graph.link(inner, outer, Direction::Out, middle, open);
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// The walls of the maze, merged into as few borders as possible.
+    pub fn to_borders(&self) -> Vec<Border> {
+        let mut borders = Vec::new();
+
+        for (circle, coords) in self.circles.iter().enumerate() {
+            let walls: Vec<bool> = coords
+                .iter()
+                .enumerate()
+                .map(|(index, coord)| {
+                    let width = arc_width(&coord.angle, &coords[(index + 1) % coords.len()].angle);
+                    match self.across(circle, &coord.angle, &width) {
+                        Some((inner, outer, middle)) => !self.is_open_at(inner, outer, &middle),
+                        None => true,
+                    }
+                })
+                .collect();
+
+            let Some(open) = walls.iter().position(|wall| !wall) else {
+                borders.push(Border {
+                    start: coords[0].clone(),
+                    end: coords[0].clone(),
+                });
+                continue;
+            };
+            let mut start = None;
+            for step in 1..=coords.len() {
+                let index = (open + step) % coords.len();
+                match (walls[index], start) {
+                    (true, None) => start = Some(index),
+                    (false, Some(first)) => {
+                        borders.push(Border {
+                            start: coords[first].clone(),
+                            end: coords[index].clone(),
+                        });
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut lines: BTreeSet<(Angle, u32)> = BTreeSet::new();
+        for (ring, positions) in self.ring_positions.iter().enumerate() {
+            let start = self.ring_starts[ring];
+            for (index, angle) in positions.iter().enumerate() {
+                let before = start + (index + positions.len() - 1) % positions.len();
+                if !self.is_open_at(before, start + index, angle) {
+                    lines.insert((*angle, ring as u32));
+                }
+            }
+        }
+        let mut lines = lines.into_iter().peekable();
+        while let Some((angle, first)) = lines.next() {
+            let mut last = first;
+            while lines.next_if(|line| line == &(angle, last + 1)).is_some() {
+                last += 1;
+            }
+            borders.push(Border {
+                start: CircleCoordinate {
+                    circle: first,
+                    angle,
+                },
+                end: CircleCoordinate {
+                    circle: last + 1,
+                    angle,
+                },
+            });
+        }
+
+        borders
+    }
+
+    /// The coordinates of the grid, innermost circle first.
+    pub fn circles(&self) -> &[Vec<CircleCoordinate>] {
+        &self.circles
+    }
+
+    pub fn openings(&self) -> &Openings {
+        &self.openings
+    }
+
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    pub fn cell(&self, cell: CellId) -> &Cell {
+        &self.cells[cell]
+    }
+
+    /// The passages to all neighbours of the cell, open or not.
+    pub fn neighbours(&self, cell: CellId) -> &[Passage] {
+        &self.passages[cell]
+    }
+
+    pub fn open_neighbours(&self, cell: CellId) -> impl Iterator<Item = &Passage> {
+        self.passages[cell].iter().filter(|passage| passage.open)
+    }
+
+    pub fn entrance(&self) -> CellId {
+        self.entrance
+    }
+
+    pub fn exit(&self) -> Option<CellId> {
+        self.exit
+    }
+
+    /// Number of rings, not counting the centre.
+    pub fn rings(&self) -> usize {
+        self.ring_positions.len()
+    }
+
+    /// The cell on the ring that contains the angle.
+    pub fn cell_at(&self, ring: usize, angle: &Angle) -> Option<CellId> {
+        let positions = self.ring_positions.get(ring)?;
+        let start = self.ring_starts[ring];
+        if positions.len() < 2 {
+            return Some(start);
+        }
+
+        let index = positions.partition_point(|p| p <= angle);
+        Some(if index == 0 {
+            start + positions.len() - 1
+        } else {
+            start + index - 1
+        })
+    }
+
+    /// Whether the cell is the space outside the maze beyond the entrance or
+    /// the exit.
+    pub fn is_outside(&self, cell: CellId) -> bool {
+        cell == self.entrance || Some(cell) == self.exit
+    }
+
+    /// The open passages to other cells.
+    pub fn ways(&self, cell: CellId) -> impl Iterator<Item = &Passage> {
+        self.open_neighbours(cell)
+            .filter(move |passage| passage.cell != cell)
+    }
+
+    /// Number of open passages to other cells; 1 for a dead end.
+    pub fn degree(&self, cell: CellId) -> usize {
+        self.ways(cell).count()
+    }
+
+    /// Whether the cells are connected by an open passage.
+    pub fn is_open(&self, cell1: CellId, cell2: CellId) -> bool {
+        self.passages[cell1]
+            .iter()
+            .any(|passage| passage.cell == cell2 && passage.open)
+    }
+
+    /// Whether the passage between two cells at the given angle is open. Cells
+    /// can share more than one passage, for example on a ring of two cells.
+    fn is_open_at(&self, cell1: CellId, cell2: CellId, angle: &Angle) -> bool {
+        self.passages[cell1]
+            .iter()
+            .any(|passage| passage.cell == cell2 && &passage.angle == angle && passage.open)
+    }
+
+    /// Opens or closes the passage between two neighbouring cells at the given
+    /// angle, leaving any other passage between them as it is.
+    pub fn set_open(&mut self, cell1: CellId, cell2: CellId, angle: &Angle, open: bool) {
+        for (from, to) in [(cell1, cell2), (cell2, cell1)] {
+            for passage in self.passages[from].iter_mut() {
+                if passage.cell == to && &passage.angle == angle {
+                    passage.open = open;
+                }
+            }
+        }
+    }
+
+    fn link(&mut self, from: CellId, to: CellId, direction: Direction, angle: Angle, open: bool) {
+        self.passages[from].push(Passage {
+            cell: to,
+            direction,
+            angle,
+            open,
+        });
+        if from != to {
+            self.passages[to].push(Passage {
+                cell: from,
+                direction: direction.opposite(),
+                angle,
+                open,
+            });
+        }
+    }
+
+    /// The cells on both sides of an arc of the circle, and the middle of the arc.
+    fn across(
+        &self,
+        circle: usize,
+        start: &Angle,
+        width: &Angle,
+    ) -> Option<(CellId, CellId, Angle)> {
+        let middle = turn(start + width / Angle::from(2));
+        let inner = if circle == 0 {
+            Self::CENTRE
+        } else {
+            self.cell_at(circle - 1, &middle)?
+        };
+        let outer = if circle < self.ring_positions.len() {
+            self.cell_at(circle, &middle)?
+        } else if covers(&self.openings.entrance, start, width) {
+            self.entrance
+        } else {
+            let exit = self.openings.exit.as_ref()?;
+            if covers(exit, start, width) {
+                self.exit?
+            } else {
+                return None;
+            }
+        };
+        Some((inner, outer, middle))
+    }
+}
+
+/// Whether the arc of the given width lies inside the opening.
+fn covers(opening: &Opening, start: &Angle, width: &Angle) -> bool {
+    let opening_width = arc_width(&opening.start.angle, &opening.end.angle);
+    let offset = clockwise_distance(&opening.start.angle, start);
+    offset < opening_width && offset + *width <= opening_width
+}
+
+struct Walls {
+    lines: BTreeSet<(u32, Angle)>,
+    /// The width of every arc by its circle and start. Walls do not overlap.
+    arcs: BTreeMap<(u32, Angle), Angle>,
+}
+
+impl Walls {
+    fn new(borders: &[Border]) -> Self {
+        let mut lines = BTreeSet::new();
+        let mut arcs = BTreeMap::new();
+        for border in borders {
+            match border.border_type() {
+                BorderType::Line => {
+                    for circle in border.start.circle..border.end.circle {
+                        lines.insert((circle, border.start.angle));
+                    }
+                }
+                BorderType::Arc => {
+                    let width = arc_width(&border.start.angle, &border.end.angle);
+                    arcs.insert((border.start.circle, border.start.angle), width);
+                }
+            }
+        }
+
+        Walls { lines, arcs }
+    }
+
+    /// Whether the line between circle `ring` and the next circle is a wall.
+    fn has_line(&self, ring: u32, angle: &Angle) -> bool {
+        self.lines.contains(&(ring, *angle))
+    }
+
+    /// Whether the arc of the given width on the circle is covered by a wall.
+    fn has_arc(&self, circle: u32, start: &Angle, width: &Angle) -> bool {
+        // Only the last arc to start before it, or the last arc on the circle
+        // when that wraps past 0, can cover it.
+        let on_circle = (circle, Angle::from(0))..=(circle, *start);
+        let before = self.arcs.range(on_circle).next_back();
+        let last = self
+            .arcs
+            .range((circle, Angle::from(0))..(circle + 1, Angle::from(0)))
+            .next_back();
+        [before, last]
+            .into_iter()
+            .flatten()
+            .any(|((_, arc_start), arc_width)| {
+                let offset = clockwise_distance(arc_start, start);
+                offset < *arc_width && offset + *width <= *arc_width
+            })
+    }
+}
+
+/// The angle to travel clockwise from `from` to `to`, in `[0, 1)`.
+pub fn clockwise_distance(from: &Angle, to: &Angle) -> Angle {
+    turn(to - from)
+}
+
+/// Like [`clockwise_distance`], but an arc that starts where it ends is a full circle.
+fn arc_width(start: &Angle, end: &Angle) -> Angle {
+    if start == end {
+        Angle::from(1)
+    } else {
+        clockwise_distance(start, end)
+    }
+}
+
+/// Brings an angle less than a full turn off back into `[0, 1)`.
+pub fn turn(angle: Angle) -> Angle {
+    if angle < Angle::from(0) {
+        angle + Angle::from(1)
+    } else if angle >= Angle::from(1) {
+        angle - Angle::from(1)
+    } else {
+        angle
+    }
+}
+
+#[cfg(test)]
+mod graph_test {
+    use crate::{
+        maze::{
+            components::{Angle, CircleCoordinate, Direction, Opening, Openings},
+            test_utils::helper_fns::{create_border, create_coord},
+        },
+        MazeConfig,
+    };
+
+    use super::MazeGraph;
+
+    fn circles() -> Vec<Vec<CircleCoordinate>> {
+        vec![
+            vec![create_coord(0, 0, 1), create_coord(0, 1, 2)],
+            vec![
+                create_coord(1, 0, 1),
+                create_coord(1, 1, 4),
+                create_coord(1, 1, 2),
+                create_coord(1, 3, 4),
+            ],
+        ]
+    }
+
+    fn openings() -> Openings {
+        Openings {
+            entrance: Opening {
+                start: create_coord(1, 0, 1),
+                end: create_coord(1, 1, 4),
+            },
+            exit: None,
+        }
+    }
+
+    #[test]
+    fn test_cells_and_neighbours() {
+        let borders = vec![
+            create_border(1, 1, 4, 1, 0, 1),
+            create_border(0, 0, 1, 1, 0, 1),
+            create_border(0, 1, 2, 0, 0, 1),
+        ];
+        let graph = MazeGraph::from_borders(&circles(), &borders, &openings());
+
+        // The centre, two cells on ring 0 and the entrance.
+        assert_eq!(4, graph.cells().len());
+        assert_eq!(None, graph.exit());
+        assert_eq!(3, graph.entrance());
+
+        let centre: Vec<_> = graph
+            .neighbours(MazeGraph::CENTRE)
+            .iter()
+            .map(|p| (p.cell, p.direction, p.open))
+            .collect();
+        assert_eq!(
+            vec![(1, Direction::Out, true), (2, Direction::Out, false)],
+            centre
+        );
+
+        let cell = graph.neighbours(1);
+        assert!(cell.contains(&super::Passage {
+            cell: 2,
+            direction: Direction::CounterClockwise,
+            angle: Angle::from(0),
+            open: false,
+        }));
+        assert!(cell.contains(&super::Passage {
+            cell: 2,
+            direction: Direction::Clockwise,
+            angle: Angle::new(1_u32, 2_u32),
+            open: true,
+        }));
+        assert!(graph.is_open(1, 3));
+        assert!(graph.neighbours(2).iter().all(|p| p.cell != 3));
+    }
+
+    #[test]
+    fn test_multiple_outward_neighbours() {
+        let maze = MazeConfig::new()
+            .seed(1)
+            .circles(4)
+            .inner_slices(3)
+            .build()
+            .unwrap();
+        let graph = maze.graph();
+        let outward = graph
+            .neighbours(1)
+            .iter()
+            .filter(|p| p.direction == Direction::Out)
+            .count();
+        assert_eq!(2, outward);
+    }
+
+    #[test]
+    fn test_set_open() {
+        let borders = vec![create_border(1, 1, 4, 1, 0, 1)];
+        let mut graph = MazeGraph::from_borders(&circles(), &borders, &openings());
+        assert!(graph.is_open(1, 2));
+        graph.set_open(2, 1, &Angle::from(0), false);
+        graph.set_open(2, 1, &Angle::new(1_u32, 2_u32), false);
+        assert!(!graph.is_open(1, 2));
+        assert!(!graph.is_open(2, 1));
+    }
+
+    #[test]
+    fn test_set_open_one_of_two_passages() {
+        // Both cells of ring 0 share the lines at 0 and at 1/2.
+        let borders = vec![
+            create_border(1, 1, 4, 1, 0, 1),
+            create_border(0, 0, 1, 1, 0, 1),
+            create_border(0, 1, 2, 1, 1, 2),
+        ];
+        let mut graph = MazeGraph::from_borders(&circles(), &borders, &openings());
+        assert!(!graph.is_open(1, 2));
+
+        let half = Angle::new(1_u32, 2_u32);
+        graph.set_open(1, 2, &half, true);
+        assert!(graph.is_open_at(1, 2, &half));
+        assert!(graph.is_open_at(2, 1, &half));
+        assert!(!graph.is_open_at(1, 2, &Angle::from(0)));
+        assert!(graph
+            .to_borders()
+            .contains(&create_border(0, 0, 1, 1, 0, 1)));
+    }
+
+    #[test]
+    fn test_to_borders() {
+        let borders = vec![
+            create_border(1, 1, 4, 1, 0, 1),
+            create_border(0, 0, 1, 1, 0, 1),
+            create_border(0, 1, 2, 0, 0, 1),
+        ];
+        let graph = MazeGraph::from_borders(&circles(), &borders, &openings());
+        assert_eq!(
+            vec![
+                create_border(0, 1, 2, 0, 0, 1),
+                create_border(1, 1, 4, 1, 0, 1),
+                create_border(0, 0, 1, 1, 0, 1),
+            ],
+            graph.to_borders()
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for seed in 0..5 {
+            let maze = MazeConfig::new().seed(seed).circles(7).build().unwrap();
+            let graph = maze.graph();
+            let borders = graph.to_borders();
+            let rebuilt = MazeGraph::from_borders(graph.circles(), &borders, graph.openings());
+            assert_eq!(graph, rebuilt);
+            assert_eq!(borders, rebuilt.to_borders());
+        }
+    }
+}
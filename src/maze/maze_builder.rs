@@ -1,77 +1,300 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::Rng;
+
+use super::algorithms::MazeAlgorithm;
 use super::components::Border;
+use super::pool::Pool;
+use crate::{Error, Result};
+
 use super::components::{
-    random_nr, BorderType, CellState, CircleCoordinate, Direction, Distributor,
+    angle_from_turn, random_nr, Bias, BorderType, CellState, CircleCoordinate, Direction,
+    Distributor, MazeRng, Opening, Openings, Placement,
 };
 
-pub fn build_maze(dist: Box<dyn Distributor>) -> Vec<Border> {
+/// Builds the walls of a maze and returns them with its openings and the grid
+/// coordinates that are left once points too close together are removed.
+pub fn build_maze(
+    dist: Box<dyn Distributor>,
+    rng: MazeRng,
+    algorithm: &dyn MazeAlgorithm,
+    bias: Bias,
+    entrance: Placement,
+    exit: Option<Placement>,
+) -> Result<(Vec<Border>, Openings, Vec<Vec<CircleCoordinate>>)> {
     let mut maze = MazeBuilder {
         dist,
-        borders: Vec::new(),
+        rng,
+        bias,
+        walls: Walls::default(),
     };
-    maze.create_borders();
-    maze.borders
+    let openings = maze.create_borders(algorithm, entrance, exit)?;
+    Ok((maze.walls.into_borders(), openings, maze.dist.coords()))
 }
 
-struct MazeBuilder {
+/// Grows the walls of a maze. The outer circle is taken before a
+/// [`MazeAlgorithm`] connects the free coordinates to it.
+pub struct MazeBuilder {
     dist: Box<dyn Distributor>,
-    borders: Vec<Border>,
+    rng: MazeRng,
+    bias: Bias,
+    walls: Walls,
 }
 
 impl MazeBuilder {
-    fn create_borders(&mut self) {
-        let (outer_coord, _status) = self.dist.take_from_outer_circle();
-        self.dist.consume_outer_circle();
-        self.borders.push(Border {
-            start: outer_coord.to_owned(),
-            end: outer_coord,
-        });
+    pub fn rng(&mut self) -> &mut MazeRng {
+        &mut self.rng
+    }
+
+    /// All coordinates that are not part of a wall yet.
+    pub fn free(&self) -> impl Iterator<Item = &CircleCoordinate> {
+        self.dist.free()
+    }
+
+    pub fn free_len(&self) -> usize {
+        self.dist.free_len()
+    }
+
+    /// The free coordinate with `n` free coordinates before it, in the order
+    /// of [`free`](MazeBuilder::free).
+    pub fn nth_free(&self, n: usize) -> Option<CircleCoordinate> {
+        self.dist.nth_free(n)
+    }
+
+    /// The number of free coordinates ordered before the coordinate.
+    pub fn free_before(&self, coord: &CircleCoordinate) -> usize {
+        self.dist.free_before(coord)
+    }
+
+    pub fn is_free(&self, coord: &CircleCoordinate) -> bool {
+        self.dist.state(coord) == CellState::Free
+    }
+
+    pub fn neighbour(
+        &self,
+        coord: &CircleCoordinate,
+        direction: Direction,
+    ) -> Option<CircleCoordinate> {
+        self.dist.neighbour(coord, &direction)
+    }
+
+    /// The neighbours of a coordinate together with the direction to reach them.
+    pub fn neighbours(&self, coord: &CircleCoordinate) -> Vec<(CircleCoordinate, Direction)> {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| {
+                self.dist
+                    .neighbour(coord, &direction)
+                    .map(|neighbour| (neighbour, direction))
+            })
+            .collect()
+    }
+
+    /// Picks one of the directions according to the [`Bias`]; `last` is the
+    /// direction of the step before. Returns the index of the direction.
+    pub fn choose(&mut self, directions: &[Direction], last: Option<Direction>) -> usize {
+        let weights: Vec<f64> = directions
+            .iter()
+            .map(|direction| self.bias.weight(*direction, Some(*direction) == last))
+            .collect();
+        self.pick(&weights)
+    }
+
+    /// Removes coordinates that are too close together before any wall is
+    /// grown, so that neighbours stay the same from then on.
+    pub fn remove_close_coords(&mut self) {
+        self.dist.remove_close_coords();
+    }
+
+    /// Adds a wall from the coordinate to its neighbour in the direction,
+    /// taking both. Returns the neighbour.
+    pub fn connect(
+        &mut self,
+        from: &CircleCoordinate,
+        direction: Direction,
+    ) -> Option<CircleCoordinate> {
+        let (to, _state) = self.dist.take_neighbour(from, &direction)?;
+        self.dist.take_neighbour(&to, &direction.opposite());
+        self.add_wall(from.to_owned(), to.to_owned(), direction);
+        Some(to)
+    }
+
+    /// Connects every free coordinate to the walls by random walks that end
+    /// as soon as they reach a wall.
+    pub fn random_walks(&mut self) -> Result<()> {
         while let Some(coord) = self.dist.take_free() {
-            self.create_path(&coord);
+            self.create_path(&coord)?;
+        }
+        Ok(())
+    }
+
+    fn create_borders(
+        &mut self,
+        algorithm: &dyn MazeAlgorithm,
+        entrance: Placement,
+        exit: Option<Placement>,
+    ) -> Result<Openings> {
+        let (entrance_coord, _status) = match entrance {
+            Placement::Random => self.dist.take_from_outer_circle(),
+            Placement::At(turn) => self.dist.take_from_outer_circle_at(&angle_from_turn(turn)),
+        };
+        let exit_coord = match exit {
+            Some(placement) => self.take_exit(placement, &entrance_coord)?,
+            None => None,
+        };
+        self.dist.consume_outer_circle();
+
+        let entrance = self.opening(entrance_coord);
+        let exit = exit_coord.map(|coord| self.opening(coord));
+        match &exit {
+            Some(exit) => {
+                self.push_outer_arc(&entrance.end, &exit.start);
+                self.push_outer_arc(&exit.end, &entrance.start);
+            }
+            None => self.walls.push(Border {
+                start: entrance.end.to_owned(),
+                end: entrance.start.to_owned(),
+            }),
+        }
+
+        algorithm.grow(self)?;
+        self.random_walks()?;
+
+        Ok(Openings { entrance, exit })
+    }
+
+    fn take_exit(
+        &mut self,
+        placement: Placement,
+        entrance: &CircleCoordinate,
+    ) -> Result<Option<CircleCoordinate>> {
+        let turn = match placement {
+            Placement::Random => self.rng.gen::<f64>(),
+            Placement::At(turn) => turn,
+        };
+        let (coord, _status) = self.dist.take_from_outer_circle_at(&angle_from_turn(turn));
+        if &coord != entrance {
+            return Ok(Some(coord));
+        }
+        if let Placement::At(turn) = placement {
+            return Err(Error::InvalidParameter(format!(
+                "the exit at {} lies on the entrance",
+                turn
+            )));
+        }
+
+        let (coord, _status) = self
+            .dist
+            .take_from_outer_circle_at(&angle_from_turn(turn + 0.5));
+        Ok((&coord != entrance).then_some(coord))
+    }
+
+    /// The outer circle is left open between the coordinate and its
+    /// clockwise neighbour.
+    fn opening(&self, coord: CircleCoordinate) -> Opening {
+        let end = self
+            .dist
+            .neighbour(&coord, &Direction::Clockwise)
+            .unwrap_or_else(|| coord.to_owned());
+        Opening { start: coord, end }
+    }
+
+    fn push_outer_arc(&mut self, start: &CircleCoordinate, end: &CircleCoordinate) {
+        if start != end {
+            self.walls.push(Border {
+                start: start.to_owned(),
+                end: end.to_owned(),
+            });
         }
     }
 
-    fn create_path(&mut self, start_coord: &CircleCoordinate) -> Vec<CircleCoordinate> {
-        let mut visited: Vec<CircleCoordinate> = vec![start_coord.to_owned()];
-        let mut options: Vec<(CircleCoordinate, Direction)> = Vec::new();
+    fn create_path(&mut self, start_coord: &CircleCoordinate) -> Result<()> {
+        let mut visited: HashSet<CircleCoordinate> = HashSet::from([start_coord.to_owned()]);
+        let mut options: Pool<(CircleCoordinate, Direction)> = Pool::default();
         let mut coord = start_coord.to_owned();
+        let mut last = None;
         let mut path_open = true;
         while path_open {
             add_options(&mut options, &coord);
-            let (from_coord, to_coord, direction, state) = self.next(&mut options, &visited);
+            let (from_coord, to_coord, direction, state) =
+                self.next(&mut options, &visited, &coord, last)?;
             coord = to_coord.to_owned();
+            last = Some(direction);
             path_open = state == CellState::Free;
 
-            visited.push(to_coord.to_owned());
-            let (merge_start, merge_end, border_type) = match direction {
-                Direction::Out => (from_coord, to_coord, BorderType::Line),
-                Direction::In => (to_coord, from_coord, BorderType::Line),
-                Direction::Clockwise => (from_coord, to_coord, BorderType::Arc),
-                Direction::CounterClockwise => (to_coord, from_coord, BorderType::Arc),
-            };
-            self.merge_borders(merge_start, merge_end, border_type);
+            visited.insert(to_coord.to_owned());
+            self.add_wall(from_coord, to_coord, direction);
         }
 
-        visited
+        Ok(())
+    }
+
+    fn add_wall(&mut self, from: CircleCoordinate, to: CircleCoordinate, direction: Direction) {
+        let (merge_start, merge_end, border_type) = match direction {
+            Direction::Out => (from, to, BorderType::Line),
+            Direction::In => (to, from, BorderType::Line),
+            Direction::Clockwise => (from, to, BorderType::Arc),
+            Direction::CounterClockwise => (to, from, BorderType::Arc),
+        };
+        self.merge_borders(merge_start, merge_end, border_type);
     }
 
     fn next(
         &mut self,
-        options: &mut Vec<(CircleCoordinate, Direction)>,
-        current_path: &[CircleCoordinate],
-    ) -> (CircleCoordinate, CircleCoordinate, Direction, CellState) {
+        options: &mut Pool<(CircleCoordinate, Direction)>,
+        current_path: &HashSet<CircleCoordinate>,
+        head: &CircleCoordinate,
+        last: Option<Direction>,
+    ) -> Result<(CircleCoordinate, CircleCoordinate, Direction, CellState)> {
         while !options.is_empty() {
-            let (candidate_start, candidate_direction) = options.remove(random_nr(options.len()));
+            let index = if self.bias == Bias::default() {
+                // Every option is equally likely, so long walks need no weights.
+                random_nr(&mut self.rng, options.len())
+            } else {
+                let weights: Vec<f64> = options
+                    .iter()
+                    .map(|(start, direction)| {
+                        self.bias
+                            .weight(*direction, start == head && Some(*direction) == last)
+                    })
+                    .collect();
+                self.pick(&weights)
+            };
+            let Some((candidate_start, candidate_direction)) = options.remove_nth(index) else {
+                break;
+            };
             let neighbour_option = self
                 .dist
                 .take_neighbour(&candidate_start, &candidate_direction);
             if let Some((end, status)) = neighbour_option {
                 if !current_path.contains(&end) {
-                    return (candidate_start, end, candidate_direction, status);
+                    return Ok((candidate_start, end, candidate_direction, status));
                 }
             }
         }
 
-        panic!();
+        Err(Error::Generation(format!(
+            "the walk at {} is stuck without reaching a wall",
+            head
+        )))
+    }
+
+    /// Picks an index with a chance proportional to its weight. Without a bias
+    /// every index is equally likely, as are all of them when every weight is 0.
+    fn pick(&mut self, weights: &[f64]) -> usize {
+        let total: f64 = weights.iter().sum();
+        if self.bias == Bias::default() || total <= 0. {
+            return random_nr(&mut self.rng, weights.len());
+        }
+
+        let mut target = self.rng.gen::<f64>() * total;
+        for (index, weight) in weights.iter().enumerate() {
+            if target < *weight {
+                return index;
+            }
+            target -= weight;
+        }
+        weights.iter().rposition(|weight| *weight > 0.).unwrap_or(0)
     }
 
     fn merge_borders(
@@ -83,46 +306,168 @@ impl MazeBuilder {
         let mut merged_start = start;
         let mut merged_end = end;
 
-        if let Some(before_index) = self.find_merge_start(&merged_start, &border_type) {
-            let before = self.borders.remove(before_index);
+        if let Some(before) = self.walls.remove_ending_at(&merged_start, border_type) {
             merged_start = before.start;
         }
 
-        if let Some(after_index) = self.find_merge_end(&merged_end, &border_type) {
-            let after = self.borders.remove(after_index);
+        if let Some(after) = self.walls.remove_starting_at(&merged_end, border_type) {
             merged_end = after.end;
         }
 
-        self.borders.push(Border {
+        self.walls.push(Border {
             start: merged_start,
             end: merged_end,
         });
     }
+}
 
-    fn find_merge_start(
-        &self,
-        from_coord: &CircleCoordinate,
-        border_type: &BorderType,
-    ) -> Option<usize> {
-        self.borders
-            .iter()
-            .position(|b| &b.border_type() == border_type && &b.end == from_coord)
+/// The borders grown so far, indexed by the coordinates they start and end
+/// at, so that a new piece of wall is merged with the walls it touches without
+/// searching all of them. Removed borders leave a gap, which keeps the others
+/// in the order they were added.
+#[derive(Default)]
+struct Walls {
+    borders: Vec<Option<Border>>,
+    by_start: HashMap<(CircleCoordinate, BorderType), usize>,
+    by_end: HashMap<(CircleCoordinate, BorderType), usize>,
+}
+
+impl Walls {
+    fn push(&mut self, border: Border) {
+        let index = self.borders.len();
+        let border_type = border.border_type();
+        self.by_start
+            .insert((border.start.clone(), border_type), index);
+        self.by_end.insert((border.end.clone(), border_type), index);
+        self.borders.push(Some(border));
     }
 
-    fn find_merge_end(
-        &self,
-        to_coord: &CircleCoordinate,
-        border_type: &BorderType,
-    ) -> Option<usize> {
-        self.borders
-            .iter()
-            .position(|b| &b.border_type() == border_type && &b.start == to_coord)
+    fn remove_starting_at(
+        &mut self,
+        coord: &CircleCoordinate,
+        border_type: BorderType,
+    ) -> Option<Border> {
+        let index = *self.by_start.get(&(coord.clone(), border_type))?;
+        self.remove(index)
+    }
+
+    fn remove_ending_at(
+        &mut self,
+        coord: &CircleCoordinate,
+        border_type: BorderType,
+    ) -> Option<Border> {
+        let index = *self.by_end.get(&(coord.clone(), border_type))?;
+        self.remove(index)
+    }
+
+    fn remove(&mut self, index: usize) -> Option<Border> {
+        let border = self.borders[index].take()?;
+        let border_type = border.border_type();
+        self.by_start.remove(&(border.start.clone(), border_type));
+        self.by_end.remove(&(border.end.clone(), border_type));
+        Some(border)
+    }
+
+    fn into_borders(self) -> Vec<Border> {
+        self.borders.into_iter().flatten().collect()
     }
 }
 
-fn add_options(options: &mut Vec<(CircleCoordinate, Direction)>, coord: &CircleCoordinate) {
+fn add_options(options: &mut Pool<(CircleCoordinate, Direction)>, coord: &CircleCoordinate) {
     options.push((coord.to_owned(), Direction::In));
     options.push((coord.to_owned(), Direction::Out));
     options.push((coord.to_owned(), Direction::Clockwise));
     options.push((coord.to_owned(), Direction::CounterClockwise));
 }
+
+#[cfg(test)]
+mod maze_builder_test {
+    use rand::SeedableRng;
+
+    use crate::{
+        maze::{
+            algorithms::RandomWalk,
+            circular_grid,
+            components::{Angle, Bias, BorderType, MazeRng, Placement},
+        },
+        Error,
+    };
+
+    use super::build_maze;
+
+    fn build_seeded(seed: u64) -> Vec<super::Border> {
+        let mut grid = circular_grid::build(6, 7, 0.3);
+        let (borders, _openings, _circles) = build_maze(
+            grid.dist(MazeRng::seed_from_u64(seed)),
+            MazeRng::seed_from_u64(seed + 1),
+            &RandomWalk,
+            Bias::default(),
+            Placement::Random,
+            None,
+        )
+        .unwrap();
+        borders
+    }
+
+    #[test]
+    fn test_same_seed_same_maze() {
+        assert_eq!(build_seeded(42), build_seeded(42));
+    }
+
+    #[test]
+    fn test_different_seed_different_maze() {
+        assert_ne!(build_seeded(42), build_seeded(43));
+    }
+
+    #[test]
+    fn test_openings() {
+        let mut grid = circular_grid::build(3, 4, 0.);
+        let (borders, openings, _circles) = build_maze(
+            grid.dist(MazeRng::seed_from_u64(1)),
+            MazeRng::seed_from_u64(2),
+            &RandomWalk,
+            Bias::default(),
+            Placement::At(0.3),
+            Some(Placement::At(0.8)),
+        )
+        .unwrap();
+
+        assert_eq!(Angle::new(1_u32, 4_u32), openings.entrance.start.angle);
+        assert_eq!(Angle::new(5_u32, 16_u32), openings.entrance.end.angle);
+        let exit = openings.exit.unwrap();
+        assert_eq!(Angle::new(3_u32, 4_u32), exit.start.angle);
+        assert_eq!(Angle::new(13_u32, 16_u32), exit.end.angle);
+
+        let outer_arcs: Vec<_> = borders
+            .iter()
+            .filter(|b| b.border_type() == BorderType::Arc && b.start.circle == 3)
+            .collect();
+        assert_eq!(2, outer_arcs.len());
+        assert_eq!(openings.entrance.end, outer_arcs[0].start);
+        assert_eq!(exit.start, outer_arcs[0].end);
+        assert_eq!(exit.end, outer_arcs[1].start);
+        assert_eq!(openings.entrance.start, outer_arcs[1].end);
+    }
+
+    #[test]
+    fn test_exit_on_entrance() {
+        let build = |exit| {
+            let mut grid = circular_grid::build(3, 4, 0.);
+            build_maze(
+                grid.dist(MazeRng::seed_from_u64(1)),
+                MazeRng::seed_from_u64(2),
+                &RandomWalk,
+                Bias::default(),
+                Placement::At(0.3),
+                Some(exit),
+            )
+        };
+
+        assert!(matches!(
+            build(Placement::At(0.3)),
+            Err(Error::InvalidParameter(_))
+        ));
+        let (_borders, openings, _circles) = build(Placement::At(0.8)).unwrap();
+        assert_ne!(Some(openings.entrance), openings.exit);
+    }
+}
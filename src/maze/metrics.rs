@@ -0,0 +1,356 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use super::{
+    components::Goal,
+    graph::{CellId, MazeGraph},
+    solver,
+};
+
+/// Numbers that describe how hard a maze is to solve. Cells are those of the
+/// [`MazeGraph`]; the space outside the maze is not counted.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Metrics {
+    pub cells: usize,
+    /// Number of cells on the shortest route from the entrance to the goal;
+    /// `None` when the goal cannot be reached.
+    pub solution_length: Option<usize>,
+    /// Cells with a single way in.
+    pub dead_ends: usize,
+    /// Cells where three or more ways meet.
+    pub junctions: usize,
+    /// The average number of ways on from a cell that is not a dead end, not
+    /// counting the way back.
+    pub branching_factor: f64,
+    /// Number of cells in the longest stretch without dead ends or junctions.
+    pub longest_corridor: usize,
+    /// The share of corridor cells that are passed straight through, without
+    /// turning. High for mazes with long flowing corridors.
+    pub river_factor: f64,
+    /// Junctions on the shortest route, where a wrong turn can be taken.
+    pub decision_points: Option<usize>,
+}
+
+impl Metrics {
+    pub fn new(graph: &MazeGraph, goal: &Goal) -> Self {
+        let cells: Vec<CellId> = (0..graph.cells().len())
+            .filter(|cell| !graph.is_outside(*cell))
+            .collect();
+        let degree = |cell: CellId| graph.degree(cell);
+
+        let dead_ends = cells.iter().filter(|cell| degree(**cell) == 1).count();
+        let junctions = cells.iter().filter(|cell| degree(**cell) >= 3).count();
+        let passable: Vec<usize> = cells
+            .iter()
+            .map(|cell| degree(*cell))
+            .filter(|degree| *degree >= 2)
+            .collect();
+        let branching_factor = ratio(
+            passable.iter().map(|degree| degree - 1).sum(),
+            passable.len(),
+        );
+
+        let corridors: Vec<CellId> = cells
+            .iter()
+            .copied()
+            .filter(|cell| degree(*cell) == 2)
+            .collect();
+        let straight = corridors
+            .iter()
+            .filter(|cell| {
+                let directions: Vec<_> = graph.ways(**cell).map(|p| p.direction).collect();
+                directions[0] == directions[1].opposite()
+            })
+            .count();
+
+        let path = solver::path(graph, goal);
+        let on_route = |path: &Vec<CellId>| -> Vec<CellId> {
+            path.iter()
+                .copied()
+                .filter(|cell| !graph.is_outside(*cell))
+                .collect()
+        };
+
+        Metrics {
+            cells: cells.len(),
+            solution_length: path.as_ref().map(|path| on_route(path).len()),
+            dead_ends,
+            junctions,
+            branching_factor,
+            longest_corridor: longest_corridor(graph, &corridors),
+            river_factor: ratio(straight, corridors.len()),
+            decision_points: path.as_ref().map(|path| {
+                let cells = on_route(path);
+                let before_goal = cells.len().saturating_sub(1);
+                cells[..before_goal]
+                    .iter()
+                    .filter(|cell| degree(**cell) >= 3)
+                    .count()
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let optional = |value: Option<usize>| match value {
+            Some(value) => value.to_string(),
+            None => "-".to_string(),
+        };
+        let rows = [
+            ("cells", self.cells.to_string()),
+            ("solution length", optional(self.solution_length)),
+            ("dead ends", self.dead_ends.to_string()),
+            ("junctions", self.junctions.to_string()),
+            ("branching factor", format!("{:.2}", self.branching_factor)),
+            ("longest corridor", self.longest_corridor.to_string()),
+            ("river factor", format!("{:.2}", self.river_factor)),
+            ("decision points", optional(self.decision_points)),
+        ];
+        for (name, value) in rows {
+            writeln!(f, "{:<18}{:>8}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A band of difficulty for a maze to fall in. Bounds that are `None` are not
+/// checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Difficulty {
+    /// The lowest and highest length of the solution in percent of all cells.
+    pub solution_percent: Option<(f64, f64)>,
+    pub min_dead_ends: Option<usize>,
+    pub max_dead_ends: Option<usize>,
+}
+
+impl Difficulty {
+    /// Whether no bound is set, so that every maze falls inside.
+    pub fn is_unbounded(&self) -> bool {
+        self == &Difficulty::default()
+    }
+
+    pub fn contains(&self, metrics: &Metrics) -> bool {
+        let solution = match (self.solution_percent, metrics.solution_length) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some((min, max)), Some(length)) => {
+                let percent = 100. * ratio(length, metrics.cells);
+                min <= percent && percent <= max
+            }
+        };
+        solution
+            && self
+                .min_dead_ends
+                .is_none_or(|min| metrics.dead_ends >= min)
+            && self
+                .max_dead_ends
+                .is_none_or(|max| metrics.dead_ends <= max)
+    }
+}
+
+fn longest_corridor(graph: &MazeGraph, corridors: &[CellId]) -> usize {
+    let mut seen = vec![false; graph.cells().len()];
+    let mut is_corridor = vec![false; graph.cells().len()];
+    for cell in corridors {
+        is_corridor[*cell] = true;
+    }
+
+    let mut longest = 0;
+    for cell in corridors {
+        if seen[*cell] {
+            continue;
+        }
+
+        seen[*cell] = true;
+        let mut stack = vec![*cell];
+        let mut length = 0;
+        while let Some(current) = stack.pop() {
+            length += 1;
+            for passage in graph.ways(current) {
+                if is_corridor[passage.cell] && !seen[passage.cell] {
+                    seen[passage.cell] = true;
+                    stack.push(passage.cell);
+                }
+            }
+        }
+        longest = longest.max(length);
+    }
+
+    longest
+}
+
+fn ratio(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod metrics_test {
+    use crate::{
+        maze::{
+            components::{CircleCoordinate, Goal, Opening, Openings},
+            graph::MazeGraph,
+            test_utils::helper_fns::{create_border, create_coord},
+        },
+        Algorithm, MazeConfig,
+    };
+
+    use super::{Difficulty, Metrics};
+
+    fn circles() -> Vec<Vec<CircleCoordinate>> {
+        vec![
+            vec![create_coord(0, 0, 1), create_coord(0, 1, 2)],
+            vec![
+                create_coord(1, 0, 1),
+                create_coord(1, 1, 4),
+                create_coord(1, 1, 2),
+                create_coord(1, 3, 4),
+            ],
+        ]
+    }
+
+    #[test]
+    fn test_metrics() {
+        let borders = vec![
+            create_border(1, 1, 4, 1, 0, 1),
+            create_border(0, 0, 1, 1, 0, 1),
+            create_border(0, 1, 2, 0, 0, 1),
+        ];
+        let openings = Openings {
+            entrance: Opening {
+                start: create_coord(1, 0, 1),
+                end: create_coord(1, 1, 4),
+            },
+            exit: None,
+        };
+        let graph = MazeGraph::from_borders(&circles(), &borders, &openings);
+
+        // The entrance leads to a junction between the centre and a dead end.
+        assert_eq!(
+            Metrics {
+                cells: 3,
+                solution_length: Some(2),
+                dead_ends: 2,
+                junctions: 1,
+                branching_factor: 2.,
+                longest_corridor: 0,
+                river_factor: 0.,
+                decision_points: Some(1),
+            },
+            Metrics::new(&graph, &Goal::Centre)
+        );
+        assert_eq!(None, Metrics::new(&graph, &Goal::Exit).solution_length);
+    }
+
+    #[test]
+    fn test_difficulty() {
+        let metrics = Metrics {
+            cells: 40,
+            solution_length: Some(10),
+            dead_ends: 6,
+            junctions: 5,
+            branching_factor: 1.2,
+            longest_corridor: 4,
+            river_factor: 0.5,
+            decision_points: Some(3),
+        };
+
+        assert!(Difficulty::default().is_unbounded());
+        assert!(Difficulty::default().contains(&metrics));
+        let band = |min, max| Difficulty {
+            solution_percent: Some((min, max)),
+            ..Difficulty::default()
+        };
+        assert!(band(20., 25.).contains(&metrics));
+        assert!(!band(26., 40.).contains(&metrics));
+        assert!(!band(0., 100.).contains(&Metrics {
+            solution_length: None,
+            ..metrics.clone()
+        }));
+
+        let dead_ends = |min, max| Difficulty {
+            min_dead_ends: min,
+            max_dead_ends: max,
+            ..Difficulty::default()
+        };
+        assert!(dead_ends(Some(6), None).contains(&metrics));
+        assert!(!dead_ends(Some(7), None).contains(&metrics));
+        assert!(dead_ends(None, Some(6)).contains(&metrics));
+        assert!(!dead_ends(Some(2), Some(5)).contains(&metrics));
+    }
+
+    #[test]
+    fn test_generated_maze() {
+        for algorithm in [Algorithm::RandomWalk, Algorithm::RecursiveBacktracker] {
+            let maze = MazeConfig::new()
+                .seed(4)
+                .circles(6)
+                .algorithm(algorithm)
+                .build()
+                .unwrap();
+            let metrics = maze.metrics();
+            let solution_length = metrics.solution_length.unwrap();
+
+            assert!(solution_length > 1);
+            assert!(metrics.decision_points.unwrap() < solution_length);
+            assert!(metrics.dead_ends > 0);
+            assert!(metrics.longest_corridor <= metrics.cells);
+            assert!((0. ..=1.).contains(&metrics.river_factor));
+        }
+    }
+
+    #[test]
+    fn test_pruned_grid() {
+        let maze = MazeConfig::new()
+            .seed(3)
+            .circles(3)
+            .inner_slices(3)
+            .min_distance(0.6)
+            .build()
+            .unwrap();
+        // The point at 5/6 on the outer circle is too close to its neighbours,
+        // which leaves a single wide cell from 2/3 to the full turn in ring 1.
+        let graph = maze.graph();
+        let outer = &graph.circles()[2];
+        assert_eq!(11, outer.len());
+        assert!(!outer.contains(&create_coord(2, 5, 6)));
+
+        // Ring 0 has cells A, B and C from angle 0, ring 1 cells D to H. The
+        // entrance leads into H, the passages are centre-C, C-A, A-B, C-H,
+        // H-D, H-G, G-F and F-E, and the route runs H, C, centre.
+        assert_eq!(
+            Metrics {
+                cells: 9,
+                solution_length: Some(3),
+                dead_ends: 4,
+                junctions: 2,
+                branching_factor: 1.6,
+                longest_corridor: 2,
+                river_factor: 1.,
+                decision_points: Some(2),
+            },
+            maze.metrics()
+        );
+    }
+
+    #[test]
+    fn test_table_and_json() {
+        let metrics = MazeConfig::new().seed(2).build().unwrap().metrics();
+
+        let table = metrics.to_string();
+        assert_eq!(8, table.lines().count());
+        assert!(table.starts_with("cells"));
+
+        let json: serde_json::Value = serde_json::to_value(&metrics).unwrap();
+        assert_eq!(metrics.dead_ends as u64, json["dead_ends"]);
+        assert_eq!(
+            metrics.solution_length.map(|length| length as u64),
+            json["solution_length"].as_u64()
+        );
+    }
+}
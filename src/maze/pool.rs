@@ -0,0 +1,163 @@
+/// A list that keeps its items in the order they were added, in which the n-th
+/// remaining item is found and removed in `O(log n)`. Picking a random position
+/// in it gives the same item as picking from a `Vec` that removes items by
+/// shifting the ones after them, without the cost of shifting.
+pub struct Pool<T> {
+    items: Vec<Option<T>>,
+    /// A Fenwick tree of the remaining items, where entry `i` counts those among
+    /// the `i & -i` slots up to slot `i - 1`.
+    tree: Vec<usize>,
+    len: usize,
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Pool {
+            items: Vec::new(),
+            tree: vec![0],
+            len: 0,
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Pool<T> {
+    fn from(items: Vec<T>) -> Self {
+        let len = items.len();
+        let mut tree = vec![1; len + 1];
+        tree[0] = 0;
+        for i in 1..=len {
+            let parent = i + lowest_bit(i);
+            if parent <= len {
+                tree[parent] += tree[i];
+            }
+        }
+
+        Pool {
+            items: items.into_iter().map(Some).collect(),
+            tree,
+            len,
+        }
+    }
+}
+
+impl<T> Pool<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds an item at the end and returns its slot.
+    pub fn push(&mut self, item: T) -> usize {
+        let slot = self.items.len();
+        let i = slot + 1;
+        let count = 1 + self.prefix(i - 1) - self.prefix(i - lowest_bit(i));
+        self.items.push(Some(item));
+        self.tree.push(count);
+        self.len += 1;
+        slot
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&T> {
+        self.items.get(slot)?.as_ref()
+    }
+
+    /// Removes the item in the slot, if it is still there.
+    pub fn remove(&mut self, slot: usize) -> Option<T> {
+        let item = self.items.get_mut(slot)?.take()?;
+        self.len -= 1;
+        let mut i = slot + 1;
+        while i < self.tree.len() {
+            self.tree[i] -= 1;
+            i += lowest_bit(i);
+        }
+        Some(item)
+    }
+
+    /// The slot of the remaining item with `n` remaining items before it.
+    pub fn nth(&self, n: usize) -> Option<usize> {
+        if n >= self.len {
+            return None;
+        }
+
+        let mut slot = 0;
+        let mut remaining = n + 1;
+        let mut step = self.items.len().next_power_of_two();
+        while step > 0 {
+            if slot + step < self.tree.len() && self.tree[slot + step] < remaining {
+                slot += step;
+                remaining -= self.tree[slot];
+            }
+            step /= 2;
+        }
+        Some(slot)
+    }
+
+    pub fn remove_nth(&mut self, n: usize) -> Option<T> {
+        let slot = self.nth(n)?;
+        self.remove(slot)
+    }
+
+    /// The number of remaining items in the slots before `slot`.
+    pub fn rank(&self, slot: usize) -> usize {
+        self.prefix(slot.min(self.items.len()))
+    }
+
+    /// The remaining items in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter().flatten()
+    }
+
+    /// The number of remaining items in the first `count` slots.
+    fn prefix(&self, count: usize) -> usize {
+        let mut sum = 0;
+        let mut i = count;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= lowest_bit(i);
+        }
+        sum
+    }
+}
+
+fn lowest_bit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+#[cfg(test)]
+mod pool_test {
+    use super::Pool;
+
+    #[test]
+    fn test_same_as_vec() {
+        let mut pool = Pool::from(vec![0, 1, 2, 3, 4]);
+        let mut items = vec![0, 1, 2, 3, 4];
+        for (step, n) in [3, 0, 2, 1, 4, 0, 5, 2, 3, 0].into_iter().enumerate() {
+            pool.push(10 + step);
+            items.push(10 + step);
+            let n = n % items.len();
+            assert_eq!(Some(items.remove(n)), pool.remove_nth(n));
+            assert_eq!(items, pool.iter().copied().collect::<Vec<_>>());
+        }
+        assert_eq!(items.len(), pool.len());
+    }
+
+    #[test]
+    fn test_slots() {
+        let mut pool = Pool::default();
+        assert_eq!(None, pool.nth(0));
+        let first = pool.push('a');
+        let second = pool.push('b');
+        assert_eq!(Some('a'), pool.remove(first));
+        assert_eq!(None, pool.remove(first));
+        assert_eq!(Some(&'b'), pool.get(second));
+        assert_eq!(Some(second), pool.nth(0));
+        assert_eq!(0, pool.rank(second));
+        assert_eq!(1, pool.rank(5));
+        assert_eq!(None, pool.nth(1));
+        assert_eq!(Some('b'), pool.remove_nth(0));
+        assert!(pool.is_empty());
+    }
+}
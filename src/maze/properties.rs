@@ -0,0 +1,162 @@
+//! Invariants that every generated maze keeps, checked over random
+//! parameters and seeds.
+
+use std::collections::HashSet;
+
+use proptest::prelude::*;
+
+use crate::{Algorithm, Maze, MazeConfig, Placement, Subdivision};
+
+use super::{
+    components::{BorderType, CircleCoordinate},
+    graph::{CellId, MazeGraph},
+};
+
+fn configs() -> impl Strategy<Value = MazeConfig> {
+    configs_with(0. ..0.9)
+}
+
+fn configs_with(min_distances: impl Strategy<Value = f64>) -> impl Strategy<Value = MazeConfig> {
+    (
+        2..9_u32,
+        1..7_u32,
+        prop::sample::select(vec![
+            Subdivision::Interleaved,
+            Subdivision::Constant,
+            Subdivision::Doubling { every: 1 },
+            Subdivision::Doubling { every: 3 },
+            Subdivision::Proportional,
+        ]),
+        min_distances,
+        prop::sample::select(Algorithm::ALL.to_vec()),
+        prop::option::of((0. ..1., 0. ..1.)),
+        any::<u64>(),
+    )
+        .prop_map(
+            |(circles, inner_slices, subdivision, min_distance, algorithm, openings, seed)| {
+                let config = MazeConfig::new()
+                    .circles(circles)
+                    .inner_slices(inner_slices)
+                    .subdivision(subdivision)
+                    .min_distance(min_distance)
+                    .algorithm(algorithm)
+                    .seed(seed);
+                match openings {
+                    Some((entrance, exit)) => config
+                        .entrance(Placement::At(entrance))
+                        .exit(Placement::At(exit)),
+                    None => config,
+                }
+            },
+        )
+        .prop_filter("too narrow cells", |config| config.validate().is_ok())
+}
+
+/// The cells that can be reached from the entrance.
+fn reachable(graph: &MazeGraph) -> HashSet<CellId> {
+    let mut reached = HashSet::from([graph.entrance()]);
+    let mut queue = vec![graph.entrance()];
+    while let Some(cell) = queue.pop() {
+        for passage in graph.ways(cell) {
+            if reached.insert(passage.cell) {
+                queue.push(passage.cell);
+            }
+        }
+    }
+    reached
+}
+
+/// Every piece of wall between two neighbouring grid coordinates.
+fn segments(maze: &Maze) -> Vec<(CircleCoordinate, CircleCoordinate)> {
+    let circles = maze.graph().circles().to_vec();
+    let mut segments = Vec::new();
+    for border in maze.borders() {
+        match border.border_type() {
+            BorderType::Line => {
+                for circle in border.start.circle..border.end.circle {
+                    let at = |circle| CircleCoordinate {
+                        circle,
+                        angle: border.start.angle,
+                    };
+                    segments.push((at(circle), at(circle + 1)));
+                }
+            }
+            BorderType::Arc => {
+                let coords = &circles[border.start.circle as usize];
+                let mut index = coords.binary_search(&border.start).unwrap();
+                loop {
+                    let next = (index + 1) % coords.len();
+                    segments.push((coords[index].clone(), coords[next].clone()));
+                    index = next;
+                    if coords[index] == border.end {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    segments
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn every_cell_is_reachable(config in configs()) {
+        let graph = config.build().unwrap().graph();
+        prop_assert_eq!(graph.cells().len(), reachable(&graph).len());
+    }
+
+    #[test]
+    fn perfect_mazes_have_one_path_between_cells(config in configs()) {
+        let graph = config.build().unwrap().graph();
+        let cells: Vec<CellId> = (0..graph.cells().len())
+            .filter(|cell| !graph.is_outside(*cell))
+            .collect();
+        // A connected graph with one passage less than cells is a tree.
+        let passages: usize = cells
+            .iter()
+            .map(|cell| graph.ways(*cell).filter(|p| !graph.is_outside(p.cell)).count())
+            .sum();
+        prop_assert_eq!(cells.len() - 1, passages / 2);
+        prop_assert_eq!(graph.cells().len(), reachable(&graph).len());
+    }
+
+    #[test]
+    fn borders_lie_on_the_grid(config in configs()) {
+        let maze = config.build().unwrap();
+        let graph = maze.graph();
+        let circles = graph.circles();
+        let outer = circles.len() as u32 - 1;
+        for border in maze.borders() {
+            for end in [&border.start, &border.end] {
+                let on_grid = circles
+                    .get(end.circle as usize)
+                    .is_some_and(|coords| coords.binary_search(end).is_ok());
+                prop_assert!(on_grid, "{} is not on the grid", end);
+            }
+            match border.border_type() {
+                BorderType::Arc => {
+                    prop_assert_eq!(border.start.circle, border.end.circle);
+                    prop_assert!(
+                        border.start != border.end || border.start.circle == outer,
+                        "{} has no length",
+                        border
+                    );
+                }
+                BorderType::Line => {
+                    prop_assert_eq!(border.start.angle, border.end.angle);
+                    prop_assert!(border.start.circle < border.end.circle, "{}", border);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn borders_do_not_overlap(config in configs()) {
+        let maze = config.build().unwrap();
+        let segments = segments(&maze);
+        let unique: HashSet<_> = segments.iter().collect();
+        prop_assert_eq!(segments.len(), unique.len());
+    }
+}
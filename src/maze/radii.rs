@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+/// How the circles of a maze are spaced out from its centre.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Spacing {
+    /// Every ring is equally wide.
+    #[default]
+    Linear,
+    /// Every ring is wider than the one inside it in proportion to its radius,
+    /// so cells keep their shape in rings with the same number of cells.
+    Logarithmic,
+    /// The radius of every circle, innermost first.
+    Custom(Vec<f64>),
+}
+
+impl Spacing {
+    /// The radii of `circles` circles, starting at `inner_radius`. The outer
+    /// circle lies as far out as with linear spacing. Custom radii ignore the
+    /// inner radius.
+    pub fn radii(&self, circles: u32, inner_radius: f64) -> Radii {
+        let rings = f64::from(circles.saturating_sub(1));
+        let outer = inner_radius + rings;
+        let radii = match self {
+            Spacing::Linear => (0..circles)
+                .map(|circle| inner_radius + f64::from(circle))
+                .collect(),
+            Spacing::Logarithmic => (0..circles)
+                .map(|circle| inner_radius * (outer / inner_radius).powf(f64::from(circle) / rings))
+                .collect(),
+            Spacing::Custom(radii) => radii.clone(),
+        };
+        Radii(radii)
+    }
+}
+
+/// The distances of the circles of a maze from its centre, innermost first.
+/// They are measured in widths of a ring of a maze with linear spacing and the
+/// default inner radius, in which circle `c` lies at radius `c + 1`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Radii(Vec<f64>);
+
+impl Radii {
+    pub fn new(radii: Vec<f64>) -> Self {
+        Radii(radii)
+    }
+
+    /// Circles 1 apart, the innermost at radius 1.
+    pub fn linear(circles: usize) -> Self {
+        Radii((1..=circles).map(|radius| radius as f64).collect())
+    }
+
+    /// The radius of a circle. Circles beyond the outer one, like the edge of
+    /// the ring that holds the openings, are spaced like the outermost ring.
+    pub fn radius(&self, circle: u32) -> f64 {
+        let circle = circle as usize;
+        match self.0.get(circle) {
+            Some(radius) => *radius,
+            None => self.outer() + (circle + 1 - self.0.len()) as f64 * self.outermost_width(),
+        }
+    }
+
+    /// The radius halfway between circle `ring` and the next one.
+    pub fn middle(&self, ring: u32) -> f64 {
+        (self.radius(ring) + self.radius(ring + 1)) / 2.
+    }
+
+    pub fn outer(&self) -> f64 {
+        self.0.last().copied().unwrap_or(0.)
+    }
+
+    /// The width of the ring inside the outer circle, or of the whole centre
+    /// when there is a single circle.
+    fn outermost_width(&self) -> f64 {
+        match self.0[..] {
+            [.., inside, outer] => outer - inside,
+            [outer] => outer,
+            [] => 0.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod radii_test {
+    use approx::abs_diff_eq;
+
+    use super::{Radii, Spacing};
+
+    #[test]
+    fn test_linear() {
+        assert_eq!(Radii::linear(4), Spacing::Linear.radii(4, 1.));
+        assert_eq!(
+            Radii::new(vec![3., 4., 5., 6.]),
+            Spacing::Linear.radii(4, 3.)
+        );
+    }
+
+    #[test]
+    fn test_logarithmic() {
+        let radii = Spacing::Logarithmic.radii(5, 2.);
+        assert_eq!(2., radii.radius(0));
+        assert!(abs_diff_eq!(6., radii.outer(), epsilon = 1e-9));
+        // Every circle is the same factor further out than the one inside it.
+        for circle in 1..4 {
+            let growth = radii.radius(circle) / radii.radius(circle - 1);
+            let next = radii.radius(circle + 1) / radii.radius(circle);
+            assert!(abs_diff_eq!(growth, next, epsilon = 1e-9));
+        }
+    }
+
+    #[test]
+    fn test_beyond_the_outer_circle() {
+        let radii = Spacing::Custom(vec![4., 5., 7.]).radii(3, 1.);
+        assert_eq!(7., radii.outer());
+        assert_eq!(9., radii.radius(3));
+        assert_eq!(8., radii.middle(2));
+        assert_eq!(3., Radii::linear(1).radius(2));
+    }
+}
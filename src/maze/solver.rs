@@ -0,0 +1,306 @@
+use std::collections::VecDeque;
+
+use super::{
+    components::{angle_from_turn, Angle, Border, CircleCoordinate, Direction, Goal, Openings},
+    graph::{clockwise_distance, turn, CellId, MazeGraph, Passage},
+};
+
+/// A point on the route through the maze.
+///
+/// `ring` is the space between circle `ring` and circle `ring + 1`, the point
+/// lies halfway between both circles. The ring just outside the outer circle
+/// is where the route starts. `None` is the centre of the maze.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoutePoint {
+    pub ring: Option<u32>,
+    pub angle: Angle,
+}
+
+/// Finds the shortest route from the entrance to the goal.
+///
+/// `circles` are the coordinates of the grid the borders were built on,
+/// innermost circle first.
+pub fn solve(
+    circles: &[Vec<CircleCoordinate>],
+    borders: &[Border],
+    openings: &Openings,
+    goal: &Goal,
+) -> Option<Vec<RoutePoint>> {
+    solve_graph(&MazeGraph::from_borders(circles, borders, openings), goal)
+}
+
+/// Finds the shortest route from the entrance to the goal through the open
+/// passages of the graph.
+pub fn solve_graph(graph: &MazeGraph, goal: &Goal) -> Option<Vec<RoutePoint>> {
+    let target = target(graph, goal)?;
+    let path = shortest_path(graph, target)?;
+    let mut route = route(graph, &path);
+    if let Goal::Ring { .. } = goal {
+        let last_passage = path.last()?;
+        let cell = graph.cell(target);
+        let middle = turn(cell.start + cell.width / Angle::from(2));
+        walk_cell(graph, &mut route, target, &last_passage.angle, &middle);
+        route.push(RoutePoint {
+            ring: cell.ring,
+            angle: middle,
+        });
+        route.dedup();
+    }
+    Some(route)
+}
+
+/// The cells on the shortest route, from the entrance to the goal.
+pub fn path(graph: &MazeGraph, goal: &Goal) -> Option<Vec<CellId>> {
+    let path = shortest_path(graph, target(graph, goal)?)?;
+    Some(
+        std::iter::once(graph.entrance())
+            .chain(path.iter().map(|passage| passage.cell))
+            .collect(),
+    )
+}
+
+fn target(graph: &MazeGraph, goal: &Goal) -> Option<CellId> {
+    match goal {
+        Goal::Centre => Some(MazeGraph::CENTRE),
+        Goal::Exit => graph.exit(),
+        Goal::Ring { ring, angle } => graph.cell_at(*ring as usize, &angle_from_turn(*angle)),
+    }
+}
+
+/// The passages taken from the entrance to the target.
+fn shortest_path(graph: &MazeGraph, target: CellId) -> Option<Vec<Passage>> {
+    let cells = graph.cells().len();
+    let mut previous: Vec<Option<(CellId, Passage)>> = vec![None; cells];
+    let mut queue = VecDeque::from([graph.entrance()]);
+    let mut seen = vec![false; cells];
+    seen[graph.entrance()] = true;
+
+    while let Some(cell) = queue.pop_front() {
+        if cell == target {
+            let mut passages = Vec::new();
+            let mut current = target;
+            while let Some((before, passage)) = &previous[current] {
+                passages.push(passage.clone());
+                current = *before;
+            }
+            passages.reverse();
+            return Some(passages);
+        }
+
+        for passage in graph.open_neighbours(cell) {
+            if !seen[passage.cell] {
+                seen[passage.cell] = true;
+                previous[passage.cell] = Some((cell, passage.clone()));
+                queue.push_back(passage.cell);
+            }
+        }
+    }
+
+    None
+}
+
+fn route(graph: &MazeGraph, path: &[Passage]) -> Vec<RoutePoint> {
+    let mut route: Vec<RoutePoint> = Vec::new();
+    let mut current = graph.entrance();
+    let mut current_angle: Option<Angle> = None;
+
+    for passage in path {
+        let angle = passage.angle;
+        if let Some(from_angle) = &current_angle {
+            walk_cell(graph, &mut route, current, from_angle, &angle);
+        }
+        route.push(RoutePoint {
+            ring: graph.cell(current).ring,
+            angle,
+        });
+        if let Direction::In | Direction::Out = passage.direction {
+            route.push(RoutePoint {
+                ring: graph.cell(passage.cell).ring,
+                angle,
+            });
+        }
+        current = passage.cell;
+        current_angle = Some(angle);
+    }
+
+    route.dedup();
+    route
+}
+
+/// Adds the points needed to get from one angle to another while staying
+/// inside the cell. Consecutive points are never half a turn or more apart.
+fn walk_cell(
+    graph: &MazeGraph,
+    route: &mut Vec<RoutePoint>,
+    cell: CellId,
+    from: &Angle,
+    to: &Angle,
+) {
+    let cell = graph.cell(cell);
+    if cell.ring.is_none() {
+        return;
+    }
+
+    let offset_from = clockwise_distance(&cell.start, from);
+    let offset_to = clockwise_distance(&cell.start, to);
+    let travel = if offset_from > offset_to {
+        offset_from - offset_to
+    } else {
+        offset_to - offset_from
+    };
+    if travel >= Angle::new(1_u32, 2_u32) {
+        route.push(RoutePoint {
+            ring: cell.ring,
+            angle: turn(cell.start + (offset_from + offset_to) / Angle::from(2)),
+        });
+    }
+}
+
+#[cfg(test)]
+mod solver_test {
+    use crate::{
+        maze::{
+            components::{Angle, CircleCoordinate, Goal, Opening, Openings},
+            test_utils::helper_fns::{create_border, create_coord},
+        },
+        MazeConfig,
+    };
+
+    use super::{solve, RoutePoint};
+
+    fn circles() -> Vec<Vec<CircleCoordinate>> {
+        vec![
+            vec![create_coord(0, 0, 1), create_coord(0, 1, 2)],
+            vec![
+                create_coord(1, 0, 1),
+                create_coord(1, 1, 4),
+                create_coord(1, 1, 2),
+                create_coord(1, 3, 4),
+            ],
+        ]
+    }
+
+    fn openings() -> Openings {
+        Openings {
+            entrance: Opening {
+                start: create_coord(1, 0, 1),
+                end: create_coord(1, 1, 4),
+            },
+            exit: Some(Opening {
+                start: create_coord(1, 1, 2),
+                end: create_coord(1, 3, 4),
+            }),
+        }
+    }
+
+    fn point(ring: Option<u32>, numer: u32, denom: u32) -> RoutePoint {
+        RoutePoint {
+            ring,
+            angle: Angle::new(numer, denom),
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let borders = vec![
+            create_border(1, 1, 4, 1, 0, 1),
+            create_border(0, 0, 1, 1, 0, 1),
+            create_border(0, 1, 2, 0, 0, 1),
+        ];
+
+        assert_eq!(
+            Some(vec![
+                point(Some(1), 1, 8),
+                point(Some(0), 1, 8),
+                point(Some(0), 1, 4),
+                point(None, 1, 4),
+            ]),
+            solve(&circles(), &borders, &openings(), &Goal::Centre)
+        );
+    }
+
+    #[test]
+    fn test_solve_around_the_ring() {
+        let borders = vec![
+            create_border(1, 1, 4, 1, 0, 1),
+            create_border(0, 0, 1, 1, 0, 1),
+            create_border(0, 0, 1, 0, 1, 2),
+        ];
+
+        assert_eq!(
+            Some(vec![
+                point(Some(1), 1, 8),
+                point(Some(0), 1, 8),
+                point(Some(0), 1, 2),
+                point(Some(0), 3, 4),
+                point(None, 3, 4),
+            ]),
+            solve(&circles(), &borders, &openings(), &Goal::Centre)
+        );
+    }
+
+    #[test]
+    fn test_solve_to_exit() {
+        let borders = vec![
+            create_border(1, 1, 4, 1, 1, 2),
+            create_border(1, 3, 4, 1, 0, 1),
+            create_border(0, 0, 1, 1, 0, 1),
+            create_border(0, 0, 1, 0, 0, 1),
+        ];
+
+        assert_eq!(
+            Some(vec![
+                point(Some(1), 1, 8),
+                point(Some(0), 1, 8),
+                point(Some(0), 1, 2),
+                point(Some(0), 5, 8),
+                point(Some(1), 5, 8),
+            ]),
+            solve(&circles(), &borders, &openings(), &Goal::Exit)
+        );
+    }
+
+    #[test]
+    fn test_solve_to_ring() {
+        let borders = vec![
+            create_border(1, 1, 4, 1, 0, 1),
+            create_border(0, 0, 1, 1, 0, 1),
+            create_border(0, 0, 1, 0, 0, 1),
+        ];
+        let goal = Goal::Ring {
+            ring: 0,
+            angle: 0.9,
+        };
+
+        assert_eq!(
+            Some(vec![
+                point(Some(1), 1, 8),
+                point(Some(0), 1, 8),
+                point(Some(0), 1, 2),
+                point(Some(0), 3, 4),
+            ]),
+            solve(&circles(), &borders, &openings(), &goal)
+        );
+    }
+
+    #[test]
+    fn test_solve_closed_maze() {
+        let borders = vec![
+            create_border(1, 0, 1, 1, 0, 1),
+            create_border(0, 0, 1, 0, 0, 1),
+        ];
+
+        assert_eq!(
+            None,
+            solve(&circles(), &borders, &openings(), &Goal::Centre)
+        );
+    }
+
+    #[test]
+    fn test_generated_mazes_are_solvable() {
+        for seed in 0..20 {
+            let maze = MazeConfig::new().seed(seed).build().unwrap();
+            assert!(maze.solution().is_some(), "no solution for seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+/// Decides into how many cells each ring of a circular grid is divided. Ring
+/// `r` lies between circles `r` and `r + 1`; the points on a circle are where
+/// the cells of the rings on both sides of it start, so the lines of both rings
+/// can end on it. The outer circle also gets the points of a ring beyond it,
+/// which the entrance and the exit are placed on.
+///
+/// Returns `None` when the number does not fit a `u32`.
+pub trait RingSubdivision {
+    fn cells(&self, ring: u32, inner_slices: u32) -> Option<u32>;
+}
+
+/// The ways to divide rings to choose from when building a maze.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Subdivision {
+    #[default]
+    Interleaved,
+    Constant,
+    Doubling {
+        every: u32,
+    },
+    Proportional,
+}
+
+impl Subdivision {
+    pub fn ring_subdivision(&self) -> Box<dyn RingSubdivision> {
+        match self {
+            Subdivision::Interleaved => Box::new(Interleaved),
+            Subdivision::Constant => Box::new(Constant),
+            Subdivision::Doubling { every } => Box::new(Doubling { every: *every }),
+            Subdivision::Proportional => Box::new(Proportional),
+        }
+    }
+}
+
+/// Every ring has `inner_slices` more cells than the one inside it, so the
+/// circles between them have points in between the lines of both rings.
+pub struct Interleaved;
+
+impl RingSubdivision for Interleaved {
+    fn cells(&self, ring: u32, inner_slices: u32) -> Option<u32> {
+        ring.checked_add(1)?.checked_mul(inner_slices)
+    }
+}
+
+/// Every ring has `inner_slices` cells, which grow wider towards the outside.
+pub struct Constant;
+
+impl RingSubdivision for Constant {
+    fn cells(&self, _ring: u32, inner_slices: u32) -> Option<u32> {
+        Some(inner_slices)
+    }
+}
+
+/// The number of cells doubles every `every` rings, splitting each cell of
+/// the ring inside in two.
+pub struct Doubling {
+    pub every: u32,
+}
+
+impl RingSubdivision for Doubling {
+    fn cells(&self, ring: u32, inner_slices: u32) -> Option<u32> {
+        let doublings = ring.checked_div(self.every)?;
+        inner_slices.checked_mul(2_u32.checked_pow(doublings)?)
+    }
+}
+
+/// The number of cells grows with the length of the ring, so every cell is
+/// about as wide as those of the innermost ring. With 9 inner slices the cells
+/// are close to square.
+pub struct Proportional;
+
+impl RingSubdivision for Proportional {
+    fn cells(&self, ring: u32, inner_slices: u32) -> Option<u32> {
+        // The middle of ring `r` lies at radius `r + 1.5`.
+        let cells = (inner_slices as f64 * (2. * ring as f64 + 3.) / 3.).round();
+        (cells <= u32::MAX as f64).then_some(cells as u32)
+    }
+}
+
+#[cfg(test)]
+mod subdivision_test {
+    use super::{Constant, Doubling, Interleaved, Proportional, RingSubdivision};
+
+    fn cells(subdivision: &dyn RingSubdivision) -> Vec<u32> {
+        (0..6)
+            .map(|ring| subdivision.cells(ring, 6).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_cells() {
+        assert_eq!(vec![6, 12, 18, 24, 30, 36], cells(&Interleaved));
+        assert_eq!(vec![6, 6, 6, 6, 6, 6], cells(&Constant));
+        assert_eq!(vec![6, 6, 12, 12, 24, 24], cells(&Doubling { every: 2 }));
+        assert_eq!(vec![6, 10, 14, 18, 22, 26], cells(&Proportional));
+    }
+
+    #[test]
+    fn test_too_many_cells() {
+        assert_eq!(None, Interleaved.cells(u32::MAX, 2));
+        assert_eq!(None, Doubling { every: 1 }.cells(40, 1));
+        assert_eq!(None, Doubling { every: 0 }.cells(1, 1));
+    }
+}
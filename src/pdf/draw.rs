@@ -0,0 +1,296 @@
+use std::{fs, path::Path};
+
+use pdf_writer::{types::LineCapStyle, Content, Pdf, Rect, Ref};
+
+use crate::{
+    svg::{
+        draw::RADIUS_INNER_CIRCLE,
+        parse::{bezier_arc, bezier_circle, Canvas, CartesianCoord, Parser},
+    },
+    Error, Maze, Result,
+};
+
+const POINTS_PER_MM: f32 = 72. / 25.4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PageSize {
+    A4,
+    Letter,
+    /// Width and height in millimetres.
+    Custom {
+        width: f32,
+        height: f32,
+    },
+}
+
+impl PageSize {
+    /// Width and height in points.
+    fn points(&self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::Letter => (612., 792.),
+            PageSize::Custom { width, height } => (width * POINTS_PER_MM, height * POINTS_PER_MM),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PdfOptions {
+    pub page_size: PageSize,
+    /// Margin around the page and between mazes in millimetres.
+    pub margin: f32,
+    /// Number of mazes next to each other on a page.
+    pub columns: u32,
+    /// Number of mazes below each other on a page.
+    pub rows: u32,
+    /// Width of the walls in points.
+    pub line_width: f32,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        PdfOptions {
+            page_size: PageSize::A4,
+            margin: 15.,
+            columns: 1,
+            rows: 1,
+            line_width: 1.,
+        }
+    }
+}
+
+/// Writes the mazes to a PDF, filling every page with `columns` by `rows` mazes.
+pub fn draw(
+    mazes: &[&Maze],
+    show_solution: bool,
+    options: &PdfOptions,
+    output: &Path,
+) -> Result<()> {
+    let (page_width, page_height) = options.page_size.points();
+    let boxes = layout(options)?;
+
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let pages = mazes.chunks(boxes.len()).collect::<Vec<_>>();
+    let page_ids: Vec<Ref> = (0..pages.len() as i32)
+        .map(|page| Ref::new(3 + page * 2))
+        .collect();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().copied())
+        .count(pages.len() as i32);
+
+    for (page_id, page_mazes) in page_ids.iter().zip(pages) {
+        let content_id = Ref::new(page_id.get() + 1);
+        pdf.page(*page_id)
+            .parent(page_tree_id)
+            .media_box(Rect::new(0., 0., page_width, page_height))
+            .contents(content_id)
+            .resources();
+
+        let mut content = Content::new();
+        content.set_line_cap(LineCapStyle::RoundCap);
+        for (maze, maze_box) in page_mazes.iter().zip(&boxes) {
+            content = draw_maze(content, maze, show_solution, maze_box, options.line_width);
+        }
+        pdf.stream(content_id, &content.finish());
+    }
+
+    fs::write(output, pdf.finish())?;
+    Ok(())
+}
+
+/// A square on the page: left, bottom and size in points.
+type MazeBox = (f32, f32, f32);
+
+fn layout(options: &PdfOptions) -> Result<Vec<MazeBox>> {
+    let (page_width, page_height) = options.page_size.points();
+    let margin = options.margin * POINTS_PER_MM;
+    let (columns, rows) = (options.columns.max(1), options.rows.max(1));
+
+    let width = (page_width - margin * (columns + 1) as f32) / columns as f32;
+    let height = (page_height - margin * (rows + 1) as f32) / rows as f32;
+    let size = width.min(height);
+    if size <= 0. {
+        return Err(Error::InvalidParameter(
+            "the margins leave no room for the mazes".to_string(),
+        ));
+    }
+
+    let mut boxes = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            let left = margin + (width + margin) * column as f32 + (width - size) / 2.;
+            let top = page_height - margin - (height + margin) * row as f32 - (height - size) / 2.;
+            boxes.push((left, top - size, size));
+        }
+    }
+    Ok(boxes)
+}
+
+fn draw_maze(
+    mut content: Content,
+    maze: &Maze,
+    show_solution: bool,
+    maze_box: &MazeBox,
+    line_width: f32,
+) -> Content {
+    let parser = Parser::new(&maze.radii(), RADIUS_INNER_CIRCLE, maze.borders());
+    let (left, bottom, size) = *maze_box;
+    let scale = size / (parser.center.0 * 2.) as f32;
+
+    content.save_state();
+    content.transform([scale, 0., 0., -scale, left, bottom + size]);
+    content.set_line_width(line_width / scale);
+    content.set_stroke_rgb(0., 0., 0.);
+    let mut canvas = parser.parse(PdfCanvas::new(content, parser.center));
+    canvas.content.stroke();
+
+    if show_solution {
+        if let Some(route) = maze.solution() {
+            canvas.content.set_line_width(line_width * 2. / scale);
+            canvas.content.set_stroke_rgb(1., 0., 0.);
+            canvas = parser.parse_route(&route, canvas);
+            canvas.content.stroke();
+        }
+    }
+
+    canvas.content.restore_state();
+    canvas.content
+}
+
+struct PdfCanvas {
+    content: Content,
+    center: CartesianCoord,
+    current: CartesianCoord,
+}
+
+impl PdfCanvas {
+    fn new(content: Content, center: CartesianCoord) -> Self {
+        PdfCanvas {
+            content,
+            center,
+            current: center,
+        }
+    }
+
+    fn curves(&mut self, curves: Vec<[CartesianCoord; 3]>) {
+        for [c1, c2, end] in curves {
+            self.content.cubic_to(
+                c1.0 as f32,
+                c1.1 as f32,
+                c2.0 as f32,
+                c2.1 as f32,
+                end.0 as f32,
+                end.1 as f32,
+            );
+        }
+    }
+}
+
+impl Canvas for PdfCanvas {
+    fn move_to(mut self, coord: CartesianCoord) -> Self {
+        self.content.move_to(coord.0 as f32, coord.1 as f32);
+        self.current = coord;
+        self
+    }
+
+    fn draw_arc(
+        mut self,
+        radius: f64,
+        _long_arc_flag: u8,
+        sweep_flag: u8,
+        coord: CartesianCoord,
+    ) -> Self {
+        let curves = bezier_arc(self.center, radius, self.current, coord, sweep_flag);
+        self.curves(curves);
+        self.current = coord;
+        self
+    }
+
+    fn draw_circle(mut self, radius: f64, center: CartesianCoord) -> Self {
+        self.content
+            .move_to((center.0 + radius) as f32, center.1 as f32);
+        self.curves(bezier_circle(center, radius));
+        self
+    }
+
+    fn draw_line(mut self, coord: CartesianCoord) -> Self {
+        self.content.line_to(coord.0 as f32, coord.1 as f32);
+        self.current = coord;
+        self
+    }
+}
+
+#[cfg(test)]
+mod draw_test {
+    use std::fs;
+
+    use approx::abs_diff_eq;
+
+    use crate::MazeConfig;
+
+    use super::{draw, layout, PageSize, PdfOptions, POINTS_PER_MM};
+
+    #[test]
+    fn test_layout_single_maze() {
+        let boxes = layout(&PdfOptions::default()).unwrap();
+        assert_eq!(1, boxes.len());
+        let (left, bottom, size) = boxes[0];
+        assert!(abs_diff_eq!(15. * POINTS_PER_MM, left, epsilon = 0.01));
+        assert!(abs_diff_eq!(180. * POINTS_PER_MM, size, epsilon = 0.01));
+        assert!(abs_diff_eq!((841.89 - size) / 2., bottom, epsilon = 0.01));
+    }
+
+    #[test]
+    fn test_layout_grid() {
+        let options = PdfOptions {
+            page_size: PageSize::Custom {
+                width: 100.,
+                height: 70.,
+            },
+            margin: 10.,
+            columns: 2,
+            rows: 2,
+            ..PdfOptions::default()
+        };
+        let boxes = layout(&options).unwrap();
+        assert_eq!(4, boxes.len());
+        for (left, bottom, size) in &boxes {
+            assert!(abs_diff_eq!(20. * POINTS_PER_MM, *size, epsilon = 0.01));
+            assert!(*left >= 10. * POINTS_PER_MM);
+            assert!(*bottom >= 10. * POINTS_PER_MM - 0.01);
+        }
+        assert!(boxes[0].1 > boxes[2].1);
+        assert!(boxes[0].0 < boxes[1].0);
+    }
+
+    #[test]
+    fn test_layout_no_room() {
+        let options = PdfOptions {
+            margin: 200.,
+            ..PdfOptions::default()
+        };
+        assert!(layout(&options).is_err());
+    }
+
+    #[test]
+    fn test_draw_pages() {
+        let mazes: Vec<_> = (0..3)
+            .map(|seed| MazeConfig::new().seed(seed).build().unwrap())
+            .collect();
+        let options = PdfOptions {
+            rows: 2,
+            ..PdfOptions::default()
+        };
+        let output = std::env::temp_dir().join("circlemaze_test_draw_pages.pdf");
+        draw(&mazes.iter().collect::<Vec<_>>(), true, &options, &output).unwrap();
+
+        let pdf = String::from_utf8_lossy(&fs::read(&output).unwrap()).into_owned();
+        fs::remove_file(&output).unwrap();
+        assert!(pdf.starts_with("%PDF"));
+        assert!(pdf.contains("/Count 2"));
+    }
+}
@@ -0,0 +1,372 @@
+use std::{fs, path::Path};
+
+use crate::{
+    maze::{components::Border, radii::Radii},
+    svg::{
+        draw::RADIUS_INNER_CIRCLE,
+        order::DrawingOrder,
+        parse::{arc_sweep, Canvas, CartesianCoord, Parser},
+    },
+    Result,
+};
+
+const HPGL_UNITS_PER_MM: f64 = 40.;
+const EPSILON: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlotFormat {
+    Gcode,
+    Hpgl,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlotOptions {
+    /// Width of an equally wide ring in millimetres.
+    pub scale: f64,
+    /// Speed of the pen or tool while drawing in millimetres per minute.
+    pub feed_rate: f64,
+    /// G-code that lifts the pen or switches the tool off.
+    pub pen_up: String,
+    /// G-code that lowers the pen or switches the tool on.
+    pub pen_down: String,
+    /// Reorders the borders to keep the distance travelled with the pen up short.
+    pub optimise: bool,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        PlotOptions {
+            scale: 10.,
+            feed_rate: 1000.,
+            pen_up: "G0 Z5".to_string(),
+            pen_down: "G1 Z0".to_string(),
+            optimise: false,
+        }
+    }
+}
+
+/// Writes the borders as plotter commands. The origin is the lower left corner
+/// of the square around the maze and the y axis points up.
+pub fn draw(
+    radii: &Radii,
+    borders: &[Border],
+    format: PlotFormat,
+    options: &PlotOptions,
+    output: &Path,
+) -> Result<()> {
+    fs::write(output, render(radii, borders, format, options))?;
+    Ok(())
+}
+
+fn render(radii: &Radii, borders: &[Border], format: PlotFormat, options: &PlotOptions) -> String {
+    let parser = Parser::new(radii, RADIUS_INNER_CIRCLE, borders);
+    let canvas = PlotCanvas::new(format, options, parser.center);
+    let canvas = if options.optimise {
+        parser.parse_in_order(&DrawingOrder::optimise(borders, radii), canvas)
+    } else {
+        parser.parse(canvas)
+    };
+    canvas.finish()
+}
+
+struct PlotCanvas<'a> {
+    format: PlotFormat,
+    options: &'a PlotOptions,
+    center: CartesianCoord,
+    current: Option<CartesianCoord>,
+    pen_down: bool,
+    commands: Vec<String>,
+}
+
+impl<'a> PlotCanvas<'a> {
+    fn new(format: PlotFormat, options: &'a PlotOptions, center: CartesianCoord) -> Self {
+        let commands = match format {
+            PlotFormat::Gcode => vec![
+                "G21".to_string(),
+                "G90".to_string(),
+                options.pen_up.clone(),
+                format!("G1 F{:.0}", options.feed_rate),
+            ],
+            PlotFormat::Hpgl => vec![
+                "IN;".to_string(),
+                "SP1;".to_string(),
+                format!("VS{:.1};", options.feed_rate / 600.),
+            ],
+        };
+
+        PlotCanvas {
+            format,
+            options,
+            center,
+            current: None,
+            pen_down: false,
+            commands,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.lift_pen();
+        match self.format {
+            PlotFormat::Gcode => {
+                self.commands.push("G0 X0 Y0".to_string());
+                self.commands.push("M2".to_string());
+            }
+            PlotFormat::Hpgl => self.commands.push("SP0;".to_string()),
+        }
+
+        let mut program = self.commands.join("\n");
+        program.push('\n');
+        program
+    }
+
+    /// Position on the machine in millimetres, with the y axis pointing up.
+    fn machine(&self, coord: CartesianCoord) -> CartesianCoord {
+        let mm = self.options.scale / RADIUS_INNER_CIRCLE as f64;
+        (coord.0 * mm, (self.center.1 * 2. - coord.1) * mm)
+    }
+
+    fn hpgl(&self, coord: CartesianCoord) -> (i64, i64) {
+        let (x, y) = self.machine(coord);
+        (
+            (x * HPGL_UNITS_PER_MM).round() as i64,
+            (y * HPGL_UNITS_PER_MM).round() as i64,
+        )
+    }
+
+    fn lift_pen(&mut self) {
+        if self.pen_down {
+            self.commands.push(match self.format {
+                PlotFormat::Gcode => self.options.pen_up.clone(),
+                PlotFormat::Hpgl => "PU;".to_string(),
+            });
+            self.pen_down = false;
+        }
+    }
+
+    fn lower_pen(&mut self) {
+        if !self.pen_down {
+            self.commands.push(match self.format {
+                PlotFormat::Gcode => self.options.pen_down.clone(),
+                PlotFormat::Hpgl => "PD;".to_string(),
+            });
+            self.pen_down = true;
+        }
+    }
+
+    fn travel(&mut self, coord: CartesianCoord) {
+        let continues = self.current.is_some_and(|current| {
+            (current.0 - coord.0).abs() < EPSILON && (current.1 - coord.1).abs() < EPSILON
+        });
+        if continues {
+            return;
+        }
+
+        self.lift_pen();
+        let command = match self.format {
+            PlotFormat::Gcode => {
+                let (x, y) = self.machine(coord);
+                format!("G0 X{} Y{}", number(x), number(y))
+            }
+            PlotFormat::Hpgl => {
+                let (x, y) = self.hpgl(coord);
+                format!("PA{},{};", x, y)
+            }
+        };
+        self.commands.push(command);
+        self.current = Some(coord);
+    }
+
+    fn arc(&mut self, sweep: f64, coord: CartesianCoord) {
+        let from = self.current.unwrap_or(coord);
+        self.lower_pen();
+        let command = match self.format {
+            PlotFormat::Gcode => {
+                let (x, y) = self.machine(coord);
+                let (from_x, from_y) = self.machine(from);
+                let (center_x, center_y) = self.machine(self.center);
+                format!(
+                    "{} X{} Y{} I{} J{}",
+                    if sweep > 0. { "G3" } else { "G2" },
+                    number(x),
+                    number(y),
+                    number(center_x - from_x),
+                    number(center_y - from_y)
+                )
+            }
+            PlotFormat::Hpgl => {
+                let (center_x, center_y) = self.hpgl(self.center);
+                format!("AA{},{},{:.3};", center_x, center_y, sweep.to_degrees())
+            }
+        };
+        self.commands.push(command);
+        self.current = Some(coord);
+    }
+}
+
+/// Formats a G-code coordinate with three decimals, without a sign for zero.
+fn number(value: f64) -> String {
+    format!("{:.3}", (value * 1000.).round() / 1000. + 0.)
+}
+
+impl<'a> Canvas for PlotCanvas<'a> {
+    fn move_to(mut self, coord: CartesianCoord) -> Self {
+        self.travel(coord);
+        self
+    }
+
+    fn draw_arc(
+        mut self,
+        _radius: f64,
+        _long_arc_flag: u8,
+        sweep_flag: u8,
+        coord: CartesianCoord,
+    ) -> Self {
+        let from = self.current.unwrap_or(coord);
+        self.arc(arc_sweep(self.center, from, coord, sweep_flag), coord);
+        self
+    }
+
+    fn draw_circle(mut self, radius: f64, center: CartesianCoord) -> Self {
+        let start = (center.0 + radius, center.1);
+        self.travel(start);
+        self.arc(std::f64::consts::TAU, start);
+        self
+    }
+
+    fn draw_line(mut self, coord: CartesianCoord) -> Self {
+        self.lower_pen();
+        let command = match self.format {
+            PlotFormat::Gcode => {
+                let (x, y) = self.machine(coord);
+                format!("G1 X{} Y{}", number(x), number(y))
+            }
+            PlotFormat::Hpgl => {
+                let (x, y) = self.hpgl(coord);
+                format!("PA{},{};", x, y)
+            }
+        };
+        self.commands.push(command);
+        self.current = Some(coord);
+        self
+    }
+}
+
+#[cfg(test)]
+mod draw_test {
+    use crate::maze::{
+        components::{Angle, Border, CircleCoordinate},
+        radii::Radii,
+    };
+
+    use super::{render, PlotFormat, PlotOptions};
+
+    fn borders() -> Vec<Border> {
+        let coord = |circle, angle| CircleCoordinate { circle, angle };
+        vec![
+            Border {
+                start: coord(0, Angle::from(0)),
+                end: coord(0, Angle::new(1_u32, 4_u32)),
+            },
+            Border {
+                start: coord(0, Angle::new(1_u32, 4_u32)),
+                end: coord(1, Angle::new(1_u32, 4_u32)),
+            },
+            Border {
+                start: coord(1, Angle::from(0)),
+                end: coord(1, Angle::from(0)),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_gcode() {
+        let gcode = render(
+            &Radii::linear(1),
+            &borders(),
+            PlotFormat::Gcode,
+            &PlotOptions::default(),
+        );
+        let expected = [
+            "G21",
+            "G90",
+            "G0 Z5",
+            "G1 F1000",
+            "G0 X30.000 Y20.000",
+            "G1 Z0",
+            "G3 X20.000 Y30.000 I-10.000 J0.000",
+            "G1 X20.000 Y40.000",
+            "G0 Z5",
+            "G0 X40.000 Y20.000",
+            "G1 Z0",
+            "G3 X40.000 Y20.000 I-20.000 J0.000",
+            "G0 Z5",
+            "G0 X0 Y0",
+            "M2",
+        ];
+        assert_eq!(expected.join("\n") + "\n", gcode);
+    }
+
+    #[test]
+    fn test_hpgl() {
+        let options = PlotOptions {
+            scale: 1.,
+            feed_rate: 600.,
+            ..PlotOptions::default()
+        };
+        let hpgl = render(&Radii::linear(1), &borders(), PlotFormat::Hpgl, &options);
+        let expected = [
+            "IN;",
+            "SP1;",
+            "VS1.0;",
+            "PA120,80;",
+            "PD;",
+            "AA80,80,90.000;",
+            "PA80,160;",
+            "PU;",
+            "PA160,80;",
+            "PD;",
+            "AA80,80,360.000;",
+            "PU;",
+            "SP0;",
+        ];
+        assert_eq!(expected.join("\n") + "\n", hpgl);
+    }
+
+    #[test]
+    fn test_optimised_gcode() {
+        let mut borders = borders();
+        borders[2] = Border {
+            start: CircleCoordinate {
+                circle: 2,
+                angle: Angle::from(0),
+            },
+            end: CircleCoordinate {
+                circle: 2,
+                angle: Angle::new(1_u32, 4_u32),
+            },
+        };
+        let options = PlotOptions {
+            optimise: true,
+            ..PlotOptions::default()
+        };
+        let gcode = render(&Radii::linear(1), &borders, PlotFormat::Gcode, &options);
+        let expected = [
+            "G21",
+            "G90",
+            "G0 Z5",
+            "G1 F1000",
+            "G0 X30.000 Y20.000",
+            "G1 Z0",
+            "G3 X20.000 Y30.000 I-10.000 J0.000",
+            "G1 X20.000 Y40.000",
+            "G0 Z5",
+            "G0 X20.000 Y50.000",
+            "G1 Z0",
+            "G2 X50.000 Y20.000 I0.000 J-30.000",
+            "G0 Z5",
+            "G0 X0 Y0",
+            "M2",
+        ];
+        assert_eq!(expected.join("\n") + "\n", gcode);
+    }
+}
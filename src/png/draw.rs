@@ -0,0 +1,192 @@
+use std::path::Path;
+
+use tiny_skia::{Color, LineCap, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+use crate::{
+    maze::{components::Border, radii::Radii, solver::RoutePoint},
+    svg::{
+        draw::RADIUS_INNER_CIRCLE,
+        parse::{bezier_arc, Canvas, CartesianCoord, Parser},
+    },
+    Error, Result,
+};
+
+const SOLUTION_COLOR: [u8; 4] = [255, 0, 0, 255];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PngOptions {
+    /// Width and height of the image in pixels.
+    pub size: u32,
+    /// Width of the walls in pixels.
+    pub line_width: f32,
+    pub foreground: [u8; 4],
+    pub background: [u8; 4],
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        PngOptions {
+            size: 800,
+            line_width: 2.,
+            foreground: [0, 0, 0, 255],
+            background: [255, 255, 255, 255],
+        }
+    }
+}
+
+pub fn draw(
+    radii: &Radii,
+    borders: &[Border],
+    solution: Option<&[RoutePoint]>,
+    options: &PngOptions,
+    output: &Path,
+) -> Result<()> {
+    render(radii, borders, solution, options)?
+        .save_png(output)
+        .map_err(|error| Error::Image(error.to_string()))?;
+    Ok(())
+}
+
+fn render(
+    radii: &Radii,
+    borders: &[Border],
+    solution: Option<&[RoutePoint]>,
+    options: &PngOptions,
+) -> Result<Pixmap> {
+    let parser = Parser::new(radii, RADIUS_INNER_CIRCLE, borders);
+    let scale = options.size as f32 / (parser.center.0 * 2.) as f32;
+
+    let mut pixmap = Pixmap::new(options.size, options.size).ok_or_else(|| {
+        Error::InvalidParameter(format!("{} is not a valid image size", options.size))
+    })?;
+    pixmap.fill(color(options.background));
+
+    let canvas = parser.parse(PngCanvas::new(parser.center));
+    canvas.stroke(&mut pixmap, options.foreground, options.line_width, scale);
+
+    if let Some(route) = solution {
+        let canvas = parser.parse_route(route, PngCanvas::new(parser.center));
+        canvas.stroke(&mut pixmap, SOLUTION_COLOR, options.line_width * 2., scale);
+    }
+
+    Ok(pixmap)
+}
+
+fn color(rgba: [u8; 4]) -> Color {
+    Color::from_rgba8(rgba[0], rgba[1], rgba[2], rgba[3])
+}
+
+struct PngCanvas {
+    path: PathBuilder,
+    center: CartesianCoord,
+    current: CartesianCoord,
+}
+
+impl PngCanvas {
+    fn new(center: CartesianCoord) -> Self {
+        PngCanvas {
+            path: PathBuilder::new(),
+            center,
+            current: center,
+        }
+    }
+
+    fn stroke(self, pixmap: &mut Pixmap, rgba: [u8; 4], line_width: f32, scale: f32) {
+        let Some(path) = self.path.finish() else {
+            return;
+        };
+
+        let mut paint = Paint::default();
+        paint.set_color(color(rgba));
+        paint.anti_alias = true;
+        let stroke = Stroke {
+            width: line_width / scale,
+            line_cap: LineCap::Round,
+            ..Stroke::default()
+        };
+        pixmap.stroke_path(
+            &path,
+            &paint,
+            &stroke,
+            Transform::from_scale(scale, scale),
+            None,
+        );
+    }
+}
+
+impl Canvas for PngCanvas {
+    fn move_to(mut self, coord: CartesianCoord) -> Self {
+        self.path.move_to(coord.0 as f32, coord.1 as f32);
+        self.current = coord;
+        self
+    }
+
+    fn draw_arc(
+        mut self,
+        radius: f64,
+        _long_arc_flag: u8,
+        sweep_flag: u8,
+        coord: CartesianCoord,
+    ) -> Self {
+        for [c1, c2, end] in bezier_arc(self.center, radius, self.current, coord, sweep_flag) {
+            self.path.cubic_to(
+                c1.0 as f32,
+                c1.1 as f32,
+                c2.0 as f32,
+                c2.1 as f32,
+                end.0 as f32,
+                end.1 as f32,
+            );
+        }
+
+        self.current = coord;
+        self
+    }
+
+    fn draw_circle(mut self, radius: f64, center: CartesianCoord) -> Self {
+        self.path
+            .push_circle(center.0 as f32, center.1 as f32, radius as f32);
+        self
+    }
+
+    fn draw_line(mut self, coord: CartesianCoord) -> Self {
+        self.path.line_to(coord.0 as f32, coord.1 as f32);
+        self.current = coord;
+        self
+    }
+}
+
+#[cfg(test)]
+mod draw_test {
+    use crate::{svg::parse::Canvas, MazeConfig};
+
+    use super::{render, PngCanvas, PngOptions};
+
+    #[test]
+    fn test_arc_ends_on_circle() {
+        let canvas = PngCanvas::new((50., 50.))
+            .move_to((70., 50.))
+            .draw_arc(20., 1, 0, (50., 70.));
+        let bounds = canvas.path.finish().unwrap().bounds();
+        assert!((bounds.left() - 30.).abs() < 0.01);
+        assert!((bounds.top() - 30.).abs() < 0.01);
+        assert!((bounds.right() - 70.).abs() < 0.01);
+        assert!((bounds.bottom() - 70.).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_render() {
+        let maze = MazeConfig::new().seed(1).build().unwrap();
+        let options = PngOptions {
+            size: 200,
+            ..PngOptions::default()
+        };
+        let pixmap = render(&maze.radii(), maze.borders(), None, &options).unwrap();
+
+        assert_eq!(200, pixmap.width());
+        assert_eq!(200, pixmap.height());
+        let corner = pixmap.pixel(0, 0).unwrap();
+        assert_eq!(255, corner.red());
+        assert!(pixmap.pixels().iter().any(|p| p.red() == 0));
+    }
+}
@@ -1,2 +1,3 @@
 pub mod draw;
+pub mod order;
 pub mod parse;
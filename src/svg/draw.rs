@@ -1,31 +1,36 @@
 use handlebars::Handlebars;
-use std::{collections::HashMap, error::Error, fs::File};
+use std::{collections::HashMap, fs::File, path::Path};
 
-use crate::maze::components::Border;
+use crate::{
+    maze::{components::Border, radii::Radii, solver::RoutePoint},
+    Result,
+};
 
 use super::parse::{Canvas, CartesianCoord, Parser};
 
-const RADIUS_INNER_CIRCLE: u32 = 10;
+pub const RADIUS_INNER_CIRCLE: u32 = 10;
 
-pub fn draw(circles: usize, borders: Vec<Border>) -> Result<(), Box<dyn Error>> {
-    let center = RADIUS_INNER_CIRCLE * (circles as u32 + 1);
-    let parser = Parser {
-        center: (center as f64, center as f64),
-        radius_inner_circle: RADIUS_INNER_CIRCLE,
-        borders,
-    };
-    let canvas = parser.parse(SvgCanvas {
-        path: String::new(),
-        circle: None,
-    });
+pub fn draw(
+    radii: &Radii,
+    borders: &[Border],
+    solution: Option<&[RoutePoint]>,
+    template: &Path,
+    output: &Path,
+) -> Result<()> {
+    let parser = Parser::new(radii, RADIUS_INNER_CIRCLE, borders);
+    let canvas = parser.parse(SvgCanvas::default());
 
     let mut data: HashMap<String, String> = HashMap::new();
-    let view_box_size = center * 2 + RADIUS_INNER_CIRCLE;
+    let view_box_size = parser.center.0 * 2. + RADIUS_INNER_CIRCLE as f64;
     data.insert(
         "view_box".to_string(),
         format!("0 0 {} {}", view_box_size, view_box_size),
     );
     data.insert("path".to_string(), canvas.path);
+    if let Some(route) = solution {
+        let solution_canvas = parser.parse_route(route, SvgCanvas::default());
+        data.insert("solution_path".to_string(), solution_canvas.path);
+    }
     if let Some(circle) = canvas.circle {
         data.insert("circle_center_x".to_string(), circle.center_x);
         data.insert("circle_center_y".to_string(), circle.center_y);
@@ -33,11 +38,9 @@ pub fn draw(circles: usize, borders: Vec<Border>) -> Result<(), Box<dyn Error>>
     }
 
     let mut handlebars = Handlebars::new();
-    handlebars
-        .register_template_file("t1", "./assets/maze.template.svg")
-        .unwrap();
+    handlebars.register_template_file("t1", template)?;
 
-    let mut output_file = File::create("maze.svg")?;
+    let mut output_file = File::create(output)?;
     handlebars.render_to_write("t1", &data, &mut output_file)?;
     Ok(())
 }
@@ -48,26 +51,40 @@ struct Circle {
     radius: String,
 }
 
-struct SvgCanvas {
+/// Collects the walls as the data of an SVG path.
+#[derive(Default)]
+pub struct SvgCanvas {
     path: String,
     circle: Option<Circle>,
 }
 
+impl SvgCanvas {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
 impl Canvas for SvgCanvas {
     fn move_to(mut self, coord: CartesianCoord) -> Self {
         self.path.push_str(&format!("M {} {} ", coord.0, coord.1));
         self
     }
 
-    fn draw_arc(mut self, radius: u32, long_arc_flag: u8, coord: CartesianCoord) -> Self {
+    fn draw_arc(
+        mut self,
+        radius: f64,
+        long_arc_flag: u8,
+        sweep_flag: u8,
+        coord: CartesianCoord,
+    ) -> Self {
         self.path.push_str(&format!(
-            "A {} {} 0 {} 0 {} {} ",
-            radius, radius, long_arc_flag, coord.0, coord.1
+            "A {} {} 0 {} {} {} {} ",
+            radius, radius, long_arc_flag, sweep_flag, coord.0, coord.1
         ));
         self
     }
 
-    fn draw_circle(mut self, radius: u32, center: CartesianCoord) -> Self {
+    fn draw_circle(mut self, radius: f64, center: CartesianCoord) -> Self {
         self.circle = Some(Circle {
             center_x: center.0.to_string(),
             center_y: center.1.to_string(),
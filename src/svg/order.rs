@@ -0,0 +1,529 @@
+use std::collections::BTreeMap;
+
+use fraction::ToPrimitive;
+
+use crate::maze::{
+    components::{Angle, Border, BorderType, CircleCoordinate},
+    radii::Radii,
+};
+
+use super::parse::CartesianCoord;
+
+/// One border drawn as part of a subpath, from its end to its start when `reversed`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stroke {
+    pub border: usize,
+    pub reversed: bool,
+}
+
+/// The order in which the borders are drawn: continuous subpaths, with the pen
+/// lifted only between two subpaths. Distances are measured in the units of
+/// [`Radii`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawingOrder {
+    subpaths: Vec<Vec<Stroke>>,
+    pen_up_before: f64,
+    pen_up_after: f64,
+}
+
+impl DrawingOrder {
+    /// Chains borders that share end points into subpaths, orders the subpaths
+    /// by nearest neighbour and improves that order with 2-opt.
+    pub fn optimise(borders: &[Border], radii: &Radii) -> Self {
+        let original: Vec<Vec<Stroke>> = (0..borders.len())
+            .map(|border| {
+                vec![Stroke {
+                    border,
+                    reversed: false,
+                }]
+            })
+            .collect();
+        let pen_up_before = pen_up_distance(borders, radii, &original);
+
+        let subpaths = two_opt(
+            borders,
+            radii,
+            nearest_neighbour(borders, radii, chain(borders)),
+        );
+        let pen_up_after = pen_up_distance(borders, radii, &subpaths);
+
+        DrawingOrder {
+            subpaths,
+            pen_up_before,
+            pen_up_after,
+        }
+    }
+
+    pub fn subpaths(&self) -> &[Vec<Stroke>] {
+        &self.subpaths
+    }
+
+    /// Distance travelled with the pen up when the borders are drawn in their original order.
+    pub fn pen_up_before(&self) -> f64 {
+        self.pen_up_before
+    }
+
+    /// Distance travelled with the pen up when the borders are drawn in this order.
+    pub fn pen_up_after(&self) -> f64 {
+        self.pen_up_after
+    }
+}
+
+/// Where drawing a stroke starts; a full circle starts and ends at angle zero.
+pub fn stroke_start(borders: &[Border], stroke: &Stroke) -> CircleCoordinate {
+    let border = &borders[stroke.border];
+    if is_circle(border) {
+        CircleCoordinate {
+            circle: border.start.circle,
+            angle: Angle::from(0),
+        }
+    } else if stroke.reversed {
+        border.end.clone()
+    } else {
+        border.start.clone()
+    }
+}
+
+/// Where drawing a stroke ends.
+pub fn stroke_end(borders: &[Border], stroke: &Stroke) -> CircleCoordinate {
+    stroke_start(
+        borders,
+        &Stroke {
+            border: stroke.border,
+            reversed: !stroke.reversed,
+        },
+    )
+}
+
+fn is_circle(border: &Border) -> bool {
+    border.border_type() == BorderType::Arc && border.start == border.end
+}
+
+fn chain(borders: &[Border]) -> Vec<Vec<Stroke>> {
+    let mut incident: BTreeMap<&CircleCoordinate, Vec<usize>> = BTreeMap::new();
+    for (index, border) in borders.iter().enumerate() {
+        if !is_circle(border) {
+            incident.entry(&border.start).or_default().push(index);
+            incident.entry(&border.end).or_default().push(index);
+        }
+    }
+
+    let mut used = vec![false; borders.len()];
+    let mut subpaths = Vec::new();
+    for (index, border) in borders.iter().enumerate() {
+        if is_circle(border) {
+            used[index] = true;
+            subpaths.push(vec![Stroke {
+                border: index,
+                reversed: false,
+            }]);
+        }
+    }
+
+    let remaining = |used: &[bool], coord: &CircleCoordinate| {
+        incident[coord].iter().filter(|&&b| !used[b]).count()
+    };
+    let mut starts: Vec<&CircleCoordinate> = incident.keys().copied().collect();
+    // Trails starting at points with an odd number of borders need fewer pen lifts.
+    starts.sort_by_key(|coord| incident[coord].len().is_multiple_of(2));
+
+    for start in starts {
+        while remaining(&used, start) > 0 {
+            let mut subpath = Vec::new();
+            let mut current = start;
+            while let Some(&border) = incident[current].iter().find(|&&b| !used[b]) {
+                used[border] = true;
+                let reversed = borders[border].start != *current;
+                subpath.push(Stroke { border, reversed });
+                current = if reversed {
+                    &borders[border].start
+                } else {
+                    &borders[border].end
+                };
+            }
+            subpaths.push(subpath);
+        }
+    }
+
+    subpaths
+}
+
+/// 2-opt only tries to join the end of a subpath to one of this many ends
+/// nearest to it.
+const NEIGHBOURS: usize = 8;
+/// 2-opt stops after this many passes over the subpaths even when it still
+/// finds improvements.
+const MAX_PASSES: usize = 16;
+
+fn nearest_neighbour(
+    borders: &[Border],
+    radii: &Radii,
+    unordered: Vec<Vec<Stroke>>,
+) -> Vec<Vec<Stroke>> {
+    if unordered.is_empty() {
+        return unordered;
+    }
+
+    let points = end_points(borders, radii, &unordered);
+    let mut buckets = Buckets::new(&points);
+    let mut unordered: Vec<Option<Vec<Stroke>>> = unordered.into_iter().map(Some).collect();
+    let mut ordered: Vec<Vec<Stroke>> = Vec::with_capacity(unordered.len());
+    // The first subpath is drawn as it is, the others from the end nearest to
+    // where the one before stopped.
+    let mut next = 0;
+    loop {
+        let subpath = next / 2;
+        buckets.remove(&points, subpath * 2);
+        buckets.remove(&points, subpath * 2 + 1);
+        let mut strokes = unordered[subpath].take().unwrap();
+        if next % 2 == 1 {
+            reverse(&mut strokes);
+        }
+        ordered.push(strokes);
+
+        match buckets.nearest(&points, points[next ^ 1], 1).first() {
+            Some(&point) => next = point,
+            None => break,
+        }
+    }
+
+    ordered
+}
+
+fn two_opt(borders: &[Border], radii: &Radii, subpaths: Vec<Vec<Stroke>>) -> Vec<Vec<Stroke>> {
+    let count = subpaths.len();
+    if count < 2 {
+        return subpaths;
+    }
+
+    let points = end_points(borders, radii, &subpaths);
+    let buckets = Buckets::new(&points);
+    let neighbours: Vec<Vec<usize>> = points
+        .iter()
+        .map(|point| buckets.nearest(&points, *point, NEIGHBOURS + 2))
+        .collect();
+    let mut tour = Tour::new(count);
+
+    for _ in 0..MAX_PASSES {
+        let mut improved = false;
+        for position in 0..count {
+            // Reversing the subpaths from `position` on to one whose end lies
+            // near the end of the subpath before.
+            if position > 0 {
+                let before = tour.end(position - 1);
+                for &point in &neighbours[before] {
+                    let subpath = point / 2;
+                    let last = tour.position[subpath];
+                    if last >= position && tour.end(last) == point {
+                        improved |= tour.improve(&points, position, last);
+                    }
+                }
+            }
+            // Reversing the subpaths up to `position` from one whose start
+            // lies near the start of the subpath after.
+            if position + 1 < count {
+                let after = tour.start(position + 1);
+                for &point in &neighbours[after] {
+                    let subpath = point / 2;
+                    let first = tour.position[subpath];
+                    if first <= position && tour.start(first) == point {
+                        improved |= tour.improve(&points, first, position);
+                    }
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    let mut subpaths: Vec<Option<Vec<Stroke>>> = subpaths.into_iter().map(Some).collect();
+    tour.order
+        .iter()
+        .map(|&subpath| {
+            let mut strokes = subpaths[subpath].take().unwrap();
+            if tour.flipped[subpath] {
+                reverse(&mut strokes);
+            }
+            strokes
+        })
+        .collect()
+}
+
+/// An order of subpaths for 2-opt. The ends of subpath `s` are the points
+/// `2 * s` and `2 * s + 1`, where it starts and ends unless it is flipped.
+struct Tour {
+    order: Vec<usize>,
+    position: Vec<usize>,
+    flipped: Vec<bool>,
+}
+
+impl Tour {
+    fn new(count: usize) -> Self {
+        Tour {
+            order: (0..count).collect(),
+            position: (0..count).collect(),
+            flipped: vec![false; count],
+        }
+    }
+
+    /// The point where the subpath at `position` starts.
+    fn start(&self, position: usize) -> usize {
+        let subpath = self.order[position];
+        subpath * 2 + usize::from(self.flipped[subpath])
+    }
+
+    fn end(&self, position: usize) -> usize {
+        self.start(position) ^ 1
+    }
+
+    /// Reverses the subpaths from `first` to `last` when that shortens the
+    /// way between them and their neighbours, and returns whether it did.
+    fn improve(&mut self, points: &[CartesianCoord], first: usize, last: usize) -> bool {
+        let between = |a: usize, b: usize| distance(points[a], points[b]);
+        let mut delta = 0.;
+        if first > 0 {
+            let before = self.end(first - 1);
+            delta += between(before, self.end(last)) - between(before, self.start(first));
+        }
+        if last + 1 < self.order.len() {
+            let after = self.start(last + 1);
+            delta += between(self.start(first), after) - between(self.end(last), after);
+        }
+        if delta >= -1e-9 {
+            return false;
+        }
+
+        self.order[first..=last].reverse();
+        for position in first..=last {
+            let subpath = self.order[position];
+            self.position[subpath] = position;
+            self.flipped[subpath] = !self.flipped[subpath];
+        }
+        true
+    }
+}
+
+/// Points sorted into square buckets about one point wide, so the points near
+/// a place are found without measuring the distance to all of them.
+struct Buckets {
+    min: CartesianCoord,
+    size: f64,
+    columns: usize,
+    rows: usize,
+    buckets: Vec<Vec<usize>>,
+}
+
+impl Buckets {
+    fn new(points: &[CartesianCoord]) -> Self {
+        let (min, max) = points.iter().fold(
+            (
+                (f64::INFINITY, f64::INFINITY),
+                (f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |(min, max), point| {
+                (
+                    (min.0.min(point.0), min.1.min(point.1)),
+                    (max.0.max(point.0), max.1.max(point.1)),
+                )
+            },
+        );
+        let extent = (max.0 - min.0).max(max.1 - min.1);
+        let size = (extent / (points.len() as f64).sqrt()).max(1e-9);
+        let columns = ((max.0 - min.0) / size) as usize + 1;
+        let rows = ((max.1 - min.1) / size) as usize + 1;
+
+        let mut buckets = Buckets {
+            min,
+            size,
+            columns,
+            rows,
+            buckets: vec![Vec::new(); columns * rows],
+        };
+        for (index, point) in points.iter().enumerate() {
+            let bucket = buckets.bucket(*point);
+            buckets.buckets[bucket.1 * columns + bucket.0].push(index);
+        }
+        buckets
+    }
+
+    fn bucket(&self, point: CartesianCoord) -> (usize, usize) {
+        let column = ((point.0 - self.min.0) / self.size).max(0.) as usize;
+        let row = ((point.1 - self.min.1) / self.size).max(0.) as usize;
+        (column.min(self.columns - 1), row.min(self.rows - 1))
+    }
+
+    fn remove(&mut self, points: &[CartesianCoord], index: usize) {
+        let (column, row) = self.bucket(points[index]);
+        let bucket = &mut self.buckets[row * self.columns + column];
+        if let Some(found) = bucket.iter().position(|&other| other == index) {
+            bucket.swap_remove(found);
+        }
+    }
+
+    /// Up to `count` of the points left, nearest to `point` first.
+    fn nearest(
+        &self,
+        points: &[CartesianCoord],
+        point: CartesianCoord,
+        count: usize,
+    ) -> Vec<usize> {
+        let (column, row) = self.bucket(point);
+        let mut found: Vec<(f64, usize)> = Vec::new();
+        for ring in 0..=self.columns.max(self.rows) {
+            let rows = row.saturating_sub(ring)..=(row + ring).min(self.rows - 1);
+            for other_row in rows {
+                let on_edge = other_row.abs_diff(row) == ring;
+                let columns = column.saturating_sub(ring)..=(column + ring).min(self.columns - 1);
+                for other_column in columns {
+                    if !on_edge && other_column.abs_diff(column) != ring {
+                        continue;
+                    }
+                    for &index in &self.buckets[other_row * self.columns + other_column] {
+                        found.push((distance(point, points[index]), index));
+                    }
+                }
+            }
+
+            // Points in buckets further out are at least this far away.
+            let beyond = ring as f64 * self.size;
+            if found.len() >= count {
+                found.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                if found[count - 1].0 <= beyond {
+                    break;
+                }
+            }
+        }
+
+        found.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        found
+            .into_iter()
+            .take(count)
+            .map(|(_, index)| index)
+            .collect()
+    }
+}
+
+fn reverse(subpath: &mut Vec<Stroke>) {
+    subpath.reverse();
+    for stroke in subpath {
+        stroke.reversed = !stroke.reversed;
+    }
+}
+
+/// Where every subpath starts and ends, at `2 * s` and `2 * s + 1` for subpath `s`.
+fn end_points(borders: &[Border], radii: &Radii, subpaths: &[Vec<Stroke>]) -> Vec<CartesianCoord> {
+    subpaths
+        .iter()
+        .flat_map(|subpath| {
+            [
+                cartesian(radii, &subpath_start(borders, subpath)),
+                cartesian(radii, &subpath_end(borders, subpath)),
+            ]
+        })
+        .collect()
+}
+
+fn subpath_start(borders: &[Border], subpath: &[Stroke]) -> CircleCoordinate {
+    stroke_start(borders, &subpath[0])
+}
+
+fn subpath_end(borders: &[Border], subpath: &[Stroke]) -> CircleCoordinate {
+    stroke_end(borders, &subpath[subpath.len() - 1])
+}
+
+fn pen_up_distance(borders: &[Border], radii: &Radii, subpaths: &[Vec<Stroke>]) -> f64 {
+    subpaths
+        .windows(2)
+        .map(|pair| {
+            distance(
+                cartesian(radii, &subpath_end(borders, &pair[0])),
+                cartesian(radii, &subpath_start(borders, &pair[1])),
+            )
+        })
+        .sum()
+}
+
+fn cartesian(radii: &Radii, coord: &CircleCoordinate) -> CartesianCoord {
+    let radius = radii.radius(coord.circle);
+    let angle = 2. * std::f64::consts::PI * coord.angle.to_f64().unwrap();
+    (radius * angle.cos(), radius * angle.sin())
+}
+
+fn distance(a: CartesianCoord, b: CartesianCoord) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+#[cfg(test)]
+mod order_test {
+    use crate::MazeConfig;
+
+    use super::{distance, stroke_end, stroke_start, Buckets};
+
+    #[test]
+    fn test_every_border_drawn_once() {
+        let maze = MazeConfig::new().seed(5).circles(8).build().unwrap();
+        let order = maze.drawing_order();
+
+        let mut drawn: Vec<usize> = order
+            .subpaths()
+            .iter()
+            .flatten()
+            .map(|stroke| stroke.border)
+            .collect();
+        drawn.sort();
+        assert_eq!((0..maze.borders().len()).collect::<Vec<_>>(), drawn);
+    }
+
+    #[test]
+    fn test_subpaths_are_continuous() {
+        let maze = MazeConfig::new().seed(6).circles(8).build().unwrap();
+        let borders = maze.borders();
+        let order = maze.drawing_order();
+
+        for subpath in order.subpaths() {
+            for pair in subpath.windows(2) {
+                assert_eq!(
+                    stroke_end(borders, &pair[0]),
+                    stroke_start(borders, &pair[1])
+                );
+            }
+        }
+        assert!(order.subpaths().len() < borders.len());
+    }
+
+    #[test]
+    fn test_pen_up_distance_reduced() {
+        for seed in 0..3 {
+            let maze = MazeConfig::new().seed(seed).circles(8).build().unwrap();
+            let order = maze.drawing_order();
+            assert!(order.pen_up_after() < order.pen_up_before());
+        }
+    }
+
+    #[test]
+    fn test_nearest_in_buckets() {
+        let points: Vec<(f64, f64)> = (0..200)
+            .map(|i| {
+                let i = i as f64;
+                ((i * 7.3) % 13. - 6., (i * 3.1) % 11. - 5.)
+            })
+            .collect();
+        let mut buckets = Buckets::new(&points);
+        for removed in 0..50 {
+            buckets.remove(&points, removed);
+        }
+
+        for point in [(0., 0.), (-6., 5.), (4.2, -1.7), (20., 20.)] {
+            let mut expected: Vec<usize> = (50..points.len()).collect();
+            expected.sort_by(|a, b| {
+                distance(point, points[*a])
+                    .partial_cmp(&distance(point, points[*b]))
+                    .unwrap()
+                    .then(a.cmp(b))
+            });
+            expected.truncate(5);
+            assert_eq!(expected, buckets.nearest(&points, point, 5));
+        }
+    }
+}
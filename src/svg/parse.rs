@@ -1,6 +1,13 @@
 use fraction::ToPrimitive;
 
-use crate::maze::components::{Angle, Border, BorderType, CircleCoordinate};
+use crate::maze::{
+    components::{Angle, Border, BorderType, CircleCoordinate},
+    graph::clockwise_distance,
+    radii::Radii,
+    solver::RoutePoint,
+};
+
+use super::order::{stroke_start, DrawingOrder};
 
 const FULL_CIRCLE: f64 = 2. * std::f64::consts::PI;
 
@@ -8,21 +15,43 @@ pub type CartesianCoord = (f64, f64);
 
 pub trait Canvas {
     fn move_to(self, coord: CartesianCoord) -> Self;
-    fn draw_arc(self, radius: u32, long_arc_flag: u8, coord: CartesianCoord) -> Self;
-    fn draw_circle(self, radius: u32, center: CartesianCoord) -> Self;
+    /// Draws an arc around the centre of the maze from the current position to
+    /// `coord`. As in SVG, a sweep flag of 0 draws with increasing angle, which is
+    /// anticlockwise on paper, and 1 draws clockwise.
+    fn draw_arc(
+        self,
+        radius: f64,
+        long_arc_flag: u8,
+        sweep_flag: u8,
+        coord: CartesianCoord,
+    ) -> Self;
+    fn draw_circle(self, radius: f64, center: CartesianCoord) -> Self;
     fn draw_line(self, coord: CartesianCoord) -> Self;
 }
 
-pub struct Parser {
+pub struct Parser<'a> {
     pub center: CartesianCoord,
-    pub radius_inner_circle: u32,
-    pub borders: Vec<Border>,
+    /// The length on the canvas of a unit of the radii.
+    pub ring_width: u32,
+    pub radii: Radii,
+    pub borders: &'a [Border],
 }
 
-impl Parser {
+impl<'a> Parser<'a> {
+    /// A parser that leaves room for one more ring around the outer circle.
+    pub fn new(radii: &Radii, ring_width: u32, borders: &'a [Border]) -> Self {
+        let center = ring_width as f64 * (radii.outer() + 1.);
+        Parser {
+            center: (center, center),
+            ring_width,
+            radii: radii.clone(),
+            borders,
+        }
+    }
+
     pub fn parse<T: Canvas>(&self, mut canvas: T) -> T {
-        for border in &self.borders {
-            let radius = (border.start.circle + 1) * self.radius_inner_circle;
+        for border in self.borders {
+            let radius = self.radius(border.start.circle);
 
             canvas = if border.border_type() == BorderType::Arc && border.start == border.end {
                 canvas.draw_circle(radius, self.center)
@@ -34,7 +63,89 @@ impl Parser {
         canvas
     }
 
-    fn arc_or_line<T: Canvas>(&self, border: &Border, radius: u32, mut canvas: T) -> T {
+    /// Draws the borders in the given order, moving the pen only between subpaths.
+    pub fn parse_in_order<T: Canvas>(&self, order: &DrawingOrder, mut canvas: T) -> T {
+        for subpath in order.subpaths() {
+            let start = stroke_start(self.borders, &subpath[0]);
+            canvas = canvas
+                .move_to(self.cartesian_coord(self.radius(start.circle), self.angle(start.angle)));
+
+            for stroke in subpath {
+                let border = &self.borders[stroke.border];
+                let radius = self.radius(border.start.circle);
+                canvas = match border.border_type() {
+                    BorderType::Arc if border.start == border.end => {
+                        canvas.draw_circle(radius, self.center)
+                    }
+                    BorderType::Arc => {
+                        let (long_arc_flag, end) =
+                            self.arc(radius, border.start.angle, &border.end);
+                        if stroke.reversed {
+                            let start =
+                                self.cartesian_coord(radius, self.angle(border.start.angle));
+                            canvas.draw_arc(radius, long_arc_flag, 1, start)
+                        } else {
+                            canvas.draw_arc(radius, long_arc_flag, 0, end)
+                        }
+                    }
+                    BorderType::Line if stroke.reversed => {
+                        canvas.draw_line(self.line(self.angle(border.start.angle), &border.start))
+                    }
+                    BorderType::Line => {
+                        canvas.draw_line(self.line(self.angle(border.start.angle), &border.end))
+                    }
+                };
+            }
+        }
+
+        canvas
+    }
+
+    /// Draws the route through the maze. Route points on the same ring are
+    /// connected by the shortest arc, other points by a straight line.
+    pub fn parse_route<T: Canvas>(&self, route: &[RoutePoint], mut canvas: T) -> T {
+        for pair in route.windows(2) {
+            let (from, to) = (&pair[0], &pair[1]);
+            let from_radius = self.route_radius(from);
+            let to_radius = self.route_radius(to);
+
+            canvas = if from.ring == to.ring {
+                let (start, end) =
+                    if clockwise_distance(&from.angle, &to.angle) <= Angle::new(1_u32, 2_u32) {
+                        (from, to)
+                    } else {
+                        (to, from)
+                    };
+                canvas
+                    .move_to(self.cartesian_coord(from_radius, self.angle(start.angle)))
+                    .draw_arc(
+                        from_radius,
+                        0,
+                        0,
+                        self.cartesian_coord(from_radius, self.angle(end.angle)),
+                    )
+            } else {
+                canvas
+                    .move_to(self.cartesian_coord(from_radius, self.angle(from.angle)))
+                    .draw_line(self.cartesian_coord(to_radius, self.angle(to.angle)))
+            };
+        }
+
+        canvas
+    }
+
+    fn radius(&self, circle: u32) -> f64 {
+        self.ring_width as f64 * self.radii.radius(circle)
+    }
+
+    fn route_radius(&self, point: &RoutePoint) -> f64 {
+        match point.ring {
+            Some(ring) => self.ring_width as f64 * self.radii.middle(ring),
+            None => 0.,
+        }
+    }
+
+    fn arc_or_line<T: Canvas>(&self, border: &Border, radius: f64, mut canvas: T) -> T {
         let angle = self.angle(border.start.angle);
         let coord = self.cartesian_coord(radius, angle);
 
@@ -42,14 +153,14 @@ impl Parser {
         match border.border_type() {
             BorderType::Arc => {
                 let (long_arc_flag, coord) = self.arc(radius, border.start.angle, &border.end);
-                canvas.draw_arc(radius, long_arc_flag, coord)
+                canvas.draw_arc(radius, long_arc_flag, 0, coord)
             }
 
             BorderType::Line => canvas.draw_line(self.line(angle, &border.end)),
         }
     }
 
-    fn arc(&self, radius: u32, start_angle: Angle, end: &CircleCoordinate) -> (u8, CartesianCoord) {
+    fn arc(&self, radius: f64, start_angle: Angle, end: &CircleCoordinate) -> (u8, CartesianCoord) {
         let diff = if start_angle > end.angle {
             Angle::from(1) - start_angle + end.angle
         } else {
@@ -63,22 +174,93 @@ impl Parser {
     }
 
     fn line(&self, angle: f64, end: &CircleCoordinate) -> (f64, f64) {
-        let end_radius = (end.circle + 1) * self.radius_inner_circle;
-        self.cartesian_coord(end_radius, angle)
+        self.cartesian_coord(self.radius(end.circle), angle)
     }
 
     fn angle(&self, angle: Angle) -> f64 {
         FULL_CIRCLE * angle.to_f64().unwrap()
     }
 
-    fn cartesian_coord(&self, radius: u32, angle: f64) -> (f64, f64) {
+    fn cartesian_coord(&self, radius: f64, angle: f64) -> (f64, f64) {
         (
-            self.center.0 + radius as f64 * angle.cos(),
-            self.center.1 - radius as f64 * angle.sin(),
+            self.center.0 + radius * angle.cos(),
+            self.center.1 - radius * angle.sin(),
         )
     }
 }
 
+/// Approximates an arc around `center`, drawn like the arcs of [`Canvas::draw_arc`],
+/// by cubic Bézier curves of at most a quarter turn each. Returns both control
+/// points and the end point of every curve.
+pub fn bezier_arc(
+    center: CartesianCoord,
+    radius: f64,
+    from: CartesianCoord,
+    to: CartesianCoord,
+    sweep_flag: u8,
+) -> Vec<[CartesianCoord; 3]> {
+    let start = (center.1 - from.1).atan2(from.0 - center.0);
+    bezier_curves(
+        center,
+        radius,
+        start,
+        arc_sweep(center, from, to, sweep_flag),
+    )
+}
+
+/// The angle in radians swept by an arc drawn like the arcs of [`Canvas::draw_arc`].
+/// A positive sweep runs anticlockwise on paper, a negative one clockwise.
+pub fn arc_sweep(
+    center: CartesianCoord,
+    from: CartesianCoord,
+    to: CartesianCoord,
+    sweep_flag: u8,
+) -> f64 {
+    let angle = |coord: CartesianCoord| (center.1 - coord.1).atan2(coord.0 - center.0);
+    let sweep = (angle(to) - angle(from)).rem_euclid(FULL_CIRCLE);
+    if sweep_flag == 0 {
+        sweep
+    } else {
+        sweep - FULL_CIRCLE
+    }
+}
+
+/// Approximates a full circle by cubic Bézier curves, starting and ending at
+/// the rightmost point of the circle.
+pub fn bezier_circle(center: CartesianCoord, radius: f64) -> Vec<[CartesianCoord; 3]> {
+    bezier_curves(center, radius, 0., FULL_CIRCLE)
+}
+
+fn bezier_curves(
+    center: CartesianCoord,
+    radius: f64,
+    start: f64,
+    sweep: f64,
+) -> Vec<[CartesianCoord; 3]> {
+    let point = |angle: f64| {
+        (
+            center.0 + radius * angle.cos(),
+            center.1 - radius * angle.sin(),
+        )
+    };
+
+    let segments = (sweep.abs() / (FULL_CIRCLE / 4.)).ceil().max(1.);
+    let step = sweep / segments;
+    let k = 4. / 3. * (step / 4.).tan() * radius;
+    (0..segments as usize)
+        .map(|segment| {
+            let a = start + step * segment as f64;
+            let b = a + step;
+            let (from, to) = (point(a), point(b));
+            [
+                (from.0 - k * a.sin(), from.1 - k * a.cos()),
+                (to.0 + k * b.sin(), to.1 + k * b.cos()),
+                to,
+            ]
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod parse_tests {
     extern crate approx;
@@ -86,7 +268,11 @@ mod parse_tests {
     use approx::abs_diff_eq;
 
     use crate::{
-        maze::components::{Angle, Border, CircleCoordinate},
+        maze::{
+            components::{Angle, Border, CircleCoordinate},
+            radii::Radii,
+            solver::RoutePoint,
+        },
         svg::parse::Canvas,
     };
 
@@ -129,28 +315,72 @@ mod parse_tests {
         let expected = DataHolder {
             params: vec![
                 Param::Move((70., 50.)),
-                Param::Arc(20, 1, (33.81966, 61.755707)),
+                Param::Arc(20., 1, 0, (33.81966, 61.755707)),
                 Param::Move((33.81966, 38.244293)),
                 Param::Line((17.63932, 26.48859)),
-                Param::Circle(60, (50., 50.)),
+                Param::Circle(60., (50., 50.)),
             ],
             index: 0,
         };
 
         let parser = Parser {
             center: (50., 50.),
-            radius_inner_circle: 20,
-            borders: path,
+            ring_width: 20,
+            radii: Radii::linear(3),
+            borders: &path,
         };
         parser.parse(expected);
     }
 
+    #[test]
+    fn test_parse_route_with_radii() {
+        let route = vec![
+            RoutePoint {
+                ring: Some(2),
+                angle: Angle::from(0),
+            },
+            RoutePoint {
+                ring: Some(1),
+                angle: Angle::from(0),
+            },
+            RoutePoint {
+                ring: Some(1),
+                angle: Angle::new(1_u32, 4_u32),
+            },
+            RoutePoint {
+                ring: None,
+                angle: Angle::new(1_u32, 4_u32),
+            },
+        ];
+        // The route runs through the middle of rings 4 wide, the outer one
+        // beyond the outer circle, and ends in the centre.
+        let expected = DataHolder {
+            params: vec![
+                Param::Move((140., 50.)),
+                Param::Line((100., 50.)),
+                Param::Move((100., 50.)),
+                Param::Arc(50., 0, 0, (50., 0.)),
+                Param::Move((50., 0.)),
+                Param::Line((50., 50.)),
+            ],
+            index: 0,
+        };
+
+        let parser = Parser {
+            center: (50., 50.),
+            ring_width: 10,
+            radii: Radii::new(vec![2., 3., 7.]),
+            borders: &[],
+        };
+        parser.parse_route(&route, expected);
+    }
+
     const EPSILON: f64 = 0.00001;
 
     enum Param {
         Move(CartesianCoord),
-        Arc(u32, u8, CartesianCoord),
-        Circle(u32, CartesianCoord),
+        Arc(f64, u8, u8, CartesianCoord),
+        Circle(f64, CartesianCoord),
         Line(CartesianCoord),
     }
 
@@ -181,17 +411,25 @@ mod parse_tests {
             panic!();
         }
 
-        fn draw_arc(self, radius: u32, long_arc_flag: u8, coord: CartesianCoord) -> Self {
-            if let Param::Arc(exp_radius, exp_flag, exp_coord) = self.params[self.index] {
+        fn draw_arc(
+            self,
+            radius: f64,
+            long_arc_flag: u8,
+            sweep_flag: u8,
+            coord: CartesianCoord,
+        ) -> Self {
+            if let Param::Arc(exp_radius, exp_flag, exp_sweep, exp_coord) = self.params[self.index]
+            {
                 assert_eq!(radius, exp_radius);
                 assert_eq!(long_arc_flag, exp_flag);
+                assert_eq!(sweep_flag, exp_sweep);
                 return self.test_coord(coord, exp_coord);
             }
 
             panic!();
         }
 
-        fn draw_circle(self, radius: u32, center: CartesianCoord) -> Self {
+        fn draw_circle(self, radius: f64, center: CartesianCoord) -> Self {
             if let Param::Circle(exp_radius, exp_center) = self.params[self.index] {
                 assert_eq!(radius, exp_radius);
                 assert_eq!(center, exp_center);